@@ -0,0 +1,267 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::Instant;
+
+use crate::level::{Level, ImportError, Segmentation, Severity, PROJECT_EXTENSION};
+use crate::tile::{TileRegistry, TileType};
+use crate::tile_manifest::TileManifest;
+
+// Headless entry point for build pipelines: everything here runs without a window or graphics context
+
+const USAGE: &str = "\
+Usage: pse_level_editor <command> [args]
+
+Commands:
+  validate <file>...                      Check that level json / project files load cleanly and pass the lint rules
+  export <project.psel> <out.json> [--name <levelID>] [--force]
+                                          Write the game json for a project; refuses levels with
+                                          validation errors unless --force is given
+  import <level.json> <out.psel> [--strict]
+                                          Convert a game json into a project
+  assign-ids <project.psel>               Give every object without an objectID a unique one, in place
+  stats <file>                            Print level size, modules and object counts
+  compare-platforms <file>...             Compare platform counts of the segmentations, with a rough
+                                          timing (mean wall time of a few runs, not a benchmark)
+  help                                    Show this message
+
+Without a command the graphical editor starts.";
+
+const COMMANDS: &[&str] = &["validate", "export", "import", "assign-ids", "stats", "compare-platforms", "help", "--help", "-h"];
+
+pub fn is_cli_invocation(args: &[String]) -> bool {
+    args.first().is_some_and(|a| COMMANDS.contains(&a.as_str()))
+}
+
+// Runs a command and returns the process exit code
+pub fn run(args: &[String], manifest_path: &str, level_height: usize) -> i32 {
+    let manifest = match TileManifest::load(manifest_path) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return 1;
+        }
+    };
+    let registry = Rc::new(TileRegistry::from_manifest_headless(&manifest));
+    let ctx = CliContext { registry, level_height };
+    let (command, rest) = match args.split_first() {
+        Some((c, rest)) => (c.as_str(), rest),
+        None => ("help", &[][..]),
+    };
+
+    let result = match command {
+        "validate" => ctx.validate(rest),
+        "export" => ctx.export(rest),
+        "import" => ctx.import(rest),
+        "assign-ids" => ctx.assign_ids(rest),
+        "stats" => ctx.stats(rest),
+        "compare-platforms" => ctx.compare_platforms(rest),
+        _ => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            1
+        }
+    }
+}
+
+struct CliContext {
+    registry: Rc<TileRegistry>,
+    level_height: usize,
+}
+
+impl CliContext {
+    // Load either a native project or a game json, depending on the file extension
+    fn load(&self, path: &str, strict: bool) -> Result<Level, String> {
+        let contents = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        let mut level = Level::new(0, self.level_height, self.registry.clone());
+        if is_project(path) {
+            level.load_project(&contents).map_err(|e| format!("{}: {}", path, e))?;
+        } else {
+            let report = match level.import_from_json(&contents, strict) {
+                Ok(report) => report,
+                Err(ImportError::Rejected(report)) => {
+                    for issue in &report.issues { eprintln!("{}: {}", path, issue); }
+                    return Err(format!("{}: {} import issue(s)", path, report.issues.len()));
+                }
+                Err(e) => return Err(format!("{}: {}", path, e)),
+            };
+            for issue in &report.issues {
+                println!("{}: {}", path, issue);
+            }
+        }
+        Ok(level)
+    }
+
+    fn validate(&self, args: &[String]) -> Result<(), String> {
+        if args.is_empty() {
+            return Err("validate needs at least one file".to_string());
+        }
+        let mut failed = 0usize;
+        for path in args {
+            // Strict, so every import issue fails the check
+            let level = match self.load(path, true) {
+                Ok(level) => level,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    failed += 1;
+                    continue;
+                }
+            };
+            let diagnostics = level.validate();
+            for d in &diagnostics {
+                println!("{}: {}", path, d);
+            }
+            if diagnostics.iter().any(|d| d.severity == Severity::Error) {
+                failed += 1;
+            } else {
+                println!("{}: ok", path);
+            }
+        }
+        if failed > 0 {
+            return Err(format!("{} of {} files failed validation", failed, args.len()));
+        }
+        Ok(())
+    }
+
+    fn export(&self, args: &[String]) -> Result<(), String> {
+        let (positional, name) = split_option(args, "--name")?;
+        let force = positional.iter().any(|a| *a == "--force");
+        let positional: Vec<&String> = positional.into_iter().filter(|a| *a != "--force").collect();
+        let [input, output] = positional.as_slice() else {
+            return Err("export needs <project.psel> <out.json>".to_string());
+        };
+        let level = self.load(input, false)?;
+        // Same gate as the editor's export: warnings are shown, errors block unless forced
+        let diagnostics = level.validate();
+        for d in &diagnostics {
+            eprintln!("{}: {}", input, d);
+        }
+        let errors = diagnostics.iter().filter(|d| d.severity == Severity::Error).count();
+        if errors > 0 && !force {
+            return Err(format!("{}: {} validation error(s), not exported (use --force to export anyway)", input, errors));
+        }
+        let name = name.unwrap_or_else(|| file_stem(output));
+        let json = level.export_to_json(name).map_err(|e| e.to_string())?;
+        std::fs::write(output, json).map_err(|e| format!("{}: {}", output, e))?;
+        println!("wrote {}", output);
+        Ok(())
+    }
+
+    fn import(&self, args: &[String]) -> Result<(), String> {
+        let strict = args.iter().any(|a| a == "--strict");
+        let positional: Vec<&String> = args.iter().filter(|a| *a != "--strict").collect();
+        let [input, output] = positional.as_slice() else {
+            return Err("import needs <level.json> <out.psel>".to_string());
+        };
+        let level = self.load(input, strict)?;
+        let json = level.save_project().map_err(|e| e.to_string())?;
+        std::fs::write(output, json).map_err(|e| format!("{}: {}", output, e))?;
+        println!("wrote {}", output);
+        Ok(())
+    }
+
+    fn assign_ids(&self, args: &[String]) -> Result<(), String> {
+        let [path] = args else {
+            return Err("assign-ids needs exactly one project file".to_string());
+        };
+        if !is_project(path) {
+            return Err(format!("{}: not a .{} project", path, PROJECT_EXTENSION));
+        }
+        let mut level = self.load(path, false)?;
+        let assigned = level.assign_missing_ids();
+        let json = level.save_project().map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| format!("{}: {}", path, e))?;
+        println!("assigned {} ids in {}", assigned, path);
+        Ok(())
+    }
+
+    fn stats(&self, args: &[String]) -> Result<(), String> {
+        let [path] = args else {
+            return Err("stats needs exactly one file".to_string());
+        };
+        let level = self.load(path, false)?;
+
+        println!("size: {} x {}", level.width(), level.height());
+        println!("modules: {} {:?}", level.modules().len(), level.modules());
+        println!("platforms: {}", level.platforms().len());
+        println!("stairs: {}", level.stairs.len());
+
+        let mut kinds: BTreeMap<String, usize> = BTreeMap::new();
+        for row in &level.tiles {
+            for tile in row {
+                if let TileType::Custom(k) = &tile.tile_type { *kinds.entry(k.clone()).or_default() += 1; }
+            }
+        }
+        println!("tiles:");
+        for (kind, count) in kinds {
+            println!("  {}: {}", kind, count);
+        }
+
+        // Game objects as the game would see them after export
+        let json = level.export_to_json(file_stem(path)).map_err(|e| e.to_string())?;
+        let doc: serde_json::Value = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+        let per_module: Vec<usize> = doc["modules"].as_array().into_iter().flatten()
+            .map(|m| m["gameObjects"].as_array().map_or(0, |g| g.len()))
+            .collect();
+        println!("game objects: {} {:?}", per_module.iter().sum::<usize>(), per_module);
+        Ok(())
+    }
+
+    // Automatic platform count per segmentation. The time is a rough mean over a few runs to spot
+    // slow files, not a benchmark; segmentation_benchmark in the segmentation tests is the measurement
+    fn compare_platforms(&self, args: &[String]) -> Result<(), String> {
+        if args.is_empty() {
+            return Err("compare-platforms needs at least one file".to_string());
+        }
+        const RUNS: u32 = 20;
+        let mut totals = vec![0usize; Segmentation::ALL.len()];
+        for path in args {
+            let level = self.load(path, false)?;
+            let mut line = path.clone();
+            for (i, segmentation) in Segmentation::ALL.into_iter().enumerate() {
+                let started = Instant::now();
+                let mut count = 0;
+                for _ in 0..RUNS { count = level.auto_platform_count(segmentation); }
+                let elapsed = started.elapsed() / RUNS;
+                totals[i] += count;
+                line.push_str(&format!("  {}: {} (~{:.2?})", segmentation.name().to_lowercase(), count, elapsed));
+            }
+            println!("{}", line);
+        }
+        if args.len() > 1 {
+            let summary: Vec<String> = Segmentation::ALL.iter().zip(&totals).map(|(s, n)| format!("{}: {}", s.name().to_lowercase(), n)).collect();
+            println!("total  {}", summary.join("  "));
+        }
+        Ok(())
+    }
+}
+
+fn is_project(path: &str) -> bool {
+    Path::new(path).extension().and_then(|e| e.to_str()) == Some(PROJECT_EXTENSION)
+}
+
+fn file_stem(path: &str) -> String {
+    Path::new(path).file_stem().and_then(|s| s.to_str()).unwrap_or("level").to_string()
+}
+
+// Pull `--flag value` out of the argument list, returning the remaining positional arguments
+fn split_option<'a>(args: &'a [String], flag: &str) -> Result<(Vec<&'a String>, Option<String>), String> {
+    let mut positional = Vec::new();
+    let mut value = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == flag {
+            value = Some(iter.next().ok_or_else(|| format!("{} needs a value", flag))?.clone());
+        } else {
+            positional.push(arg);
+        }
+    }
+    Ok((positional, value))
+}
@@ -0,0 +1,91 @@
+use std::path::{Path, PathBuf};
+use crate::level::{LibraryModule, LIBRARY_EXTENSION};
+
+// Modules saved on disk for reuse, one file per module in a single folder. The folder is read
+// when the editor starts and re-read after every change, so files copied in by hand show up too.
+pub struct ModuleLibrary {
+    dir: PathBuf,
+    entries: Vec<LibraryEntry>,
+    errors: Vec<String>, // Files that couldn't be read on the last scan
+}
+
+pub struct LibraryEntry {
+    pub path: PathBuf,
+    pub module: LibraryModule,
+    pub revision: u64, // Changes whenever the file is rewritten, so cached thumbnails can tell
+}
+
+impl ModuleLibrary {
+    pub fn open(dir: impl Into<PathBuf>) -> ModuleLibrary {
+        let mut library = ModuleLibrary { dir: dir.into(), entries: Vec::new(), errors: Vec::new() };
+        library.reload();
+        library
+    }
+
+    pub fn dir(&self) -> &Path { &self.dir }
+    pub fn entries(&self) -> &[LibraryEntry] { &self.entries }
+    pub fn errors(&self) -> &[String] { &self.errors }
+
+    // A missing folder is just an empty library; it is created on the first save
+    pub fn reload(&mut self) {
+        self.entries.clear();
+        self.errors.clear();
+        let Ok(read_dir) = std::fs::read_dir(&self.dir) else { return };
+        let mut paths: Vec<PathBuf> = read_dir
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == LIBRARY_EXTENSION))
+            .collect();
+        paths.sort();
+        for path in paths {
+            let loaded = std::fs::read_to_string(&path).map_err(|e| e.to_string())
+                .and_then(|json| LibraryModule::from_json(&json).map_err(|e| e.to_string()));
+            match loaded {
+                Ok(module) => {
+                    let revision = std::fs::metadata(&path).and_then(|m| m.modified()).ok()
+                        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                        .map_or(0, |d| d.as_nanos() as u64);
+                    self.entries.push(LibraryEntry { path, module, revision });
+                }
+                Err(e) => self.errors.push(format!("{}: {}", path.display(), e)),
+            }
+        }
+        self.entries.sort_by_key(|e| e.module.name.to_lowercase());
+    }
+
+    // Saving under an existing name replaces that module
+    pub fn save(&mut self, module: &LibraryModule) -> Result<(), String> {
+        if module.name.is_empty() { return Err("library modules need a name".to_string()); }
+        std::fs::create_dir_all(&self.dir).map_err(|e| e.to_string())?;
+        let json = module.to_json().map_err(|e| e.to_string())?;
+        let path = match self.entries.iter().find(|e| e.module.name.eq_ignore_ascii_case(&module.name)) {
+            Some(entry) => entry.path.clone(),
+            None => self.free_path(&file_stem_for(&module.name)),
+        };
+        std::fs::write(&path, json).map_err(|e| e.to_string())?;
+        self.reload();
+        Ok(())
+    }
+
+    // Different names can map to the same file name; number them instead of overwriting
+    fn free_path(&self, stem: &str) -> PathBuf {
+        let mut path = self.dir.join(format!("{}.{}", stem, LIBRARY_EXTENSION));
+        let mut n = 2;
+        while path.exists() {
+            path = self.dir.join(format!("{}_{}.{}", stem, n, LIBRARY_EXTENSION));
+            n += 1;
+        }
+        path
+    }
+
+    pub fn delete(&mut self, index: usize) -> Result<(), String> {
+        let Some(entry) = self.entries.get(index) else { return Ok(()) };
+        std::fs::remove_file(&entry.path).map_err(|e| e.to_string())?;
+        self.reload();
+        Ok(())
+    }
+}
+
+// File name for a module name: letters, digits and '-' kept, anything else becomes '_'
+fn file_stem_for(name: &str) -> String {
+    name.chars().map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' }).collect()
+}
@@ -1,13 +1,19 @@
 mod modes;
 mod mode_manager;
+mod library;
+mod workspace;
 
-pub use modes::Mode;
+pub use modes::{Mode, DrawingBrushType};
 pub use mode_manager::ModeManager;
+pub use library::{ModuleLibrary, LibraryEntry};
+pub use workspace::NewLevelSettings;
+use workspace::LevelTab;
 
 use crate::camera::Camera;
-use crate::level::Level;
+use crate::level::{Level, ImportReport, ImportError, Diagnostic, Severity, Clip, CellRect, Segmentation, HeightEdge, set_id};
 use crate::tile::{TileType, Tile, TileRegistry};
-use crate::tile_type_system::*;
+use std::rc::Rc;
+use crate::tile_manifest::{StructureKind, TileManifest};
 use egui_macroquad::macroquad::prelude::*;
 
 // Constants
@@ -19,40 +25,64 @@ const PAN_MARGIN: f32 = 5.0; // Extra margin around the level for panning
 const CAMERA_VIEWPORT_SIZE: f32 = 2.0; // Camera viewport size in world units
 
 pub struct LevelEditor {
-    level: Level,
+    level: Level, // The active tab's level and camera
     camera: Camera,
+    tabs: Vec<LevelTab>,
+    active_tab: usize,
+    closing_tab: Option<usize>, // Tab with unsaved changes waiting for the user to confirm closing it
+    show_new_level: bool,
     mode_manager: ModeManager,
     show_tile_selector: bool,
-    registry: TileRegistry,
-    tile_type_registry: TileTypeRegistry,
     show_modules: bool,
     last_right_click_pos: Vec2,
+    strict_import: bool,
+    import_outcome: Option<Result<ImportReport, ImportError>>, // Result of the last import, shown until dismissed
+    project_error: Option<String>, // Last failed project save / open, shown until dismissed
+    diagnostics: Vec<Diagnostic>,
+    show_validation: bool,
+    validation_note: Option<String>,
+    clipboard: Option<Clip>,
+    pick_metadata: bool, // Eyedropper also copies the picked tile's metadata
+    active_preset: Option<usize>, // Preset currently stamped by the drawing brush
+    library: ModuleLibrary,
+    show_library: bool,
+    library_note: Option<String>, // Outcome of the last library save / delete
 }
 
 impl LevelEditor {
-    pub async fn new(level_width: usize, level_height: usize) -> Self {
-        let mut level = Level::new(level_width, level_height);
-        // Initialize with 2 modules of size 15 (width will become 30)
-        if level.modules().is_empty() {
-            level.modules_mut().clear();
-            level.modules_mut().push(15);
-            level.modules_mut().push(15);
-            level.apply_modules_as_width();
-        }
-
+    pub async fn new(settings: &NewLevelSettings, manifest_path: &str, library_dir: &str) -> Self {
+        // A broken manifest still opens the editor, just without any tile kinds besides air
+        let manifest = TileManifest::load(manifest_path).unwrap_or_else(|e| {
+            eprintln!("failed to load tile types: {}", e);
+            TileManifest::default()
+        });
+        let registry = Rc::new(TileRegistry::from_manifest(&manifest).await);
+        let level = Level::with_modules(&settings.modules, settings.height, &settings.theme, registry);
         let camera = Camera::new(level.width() as f32, level.height() as f32);
-        let registry = TileRegistry::load_from_dir("assets/textures").await;
-        let tile_type_registry = crate::tile_types::create_tile_types().await;
 
         Self {
             level,
             camera,
+            tabs: vec![LevelTab::new(&settings.name, None)],
+            active_tab: 0,
+            closing_tab: None,
+            show_new_level: false,
             mode_manager: ModeManager::new(),
             show_tile_selector: true,
-            registry,
-            tile_type_registry,
             show_modules: false,
             last_right_click_pos: vec2(0.0, 0.0),
+            strict_import: false,
+            import_outcome: None,
+            project_error: None,
+            diagnostics: Vec::new(),
+            show_validation: false,
+            validation_note: None,
+            clipboard: None,
+            pick_metadata: false,
+            active_preset: None,
+            library: ModuleLibrary::open(library_dir),
+            show_library: false,
+            library_note: None,
         }
     }
 
@@ -69,6 +99,26 @@ impl LevelEditor {
             if is_key_pressed(KeyCode::Y) {
                 self.redo();
             }
+
+            if is_key_pressed(KeyCode::C) {
+                self.copy_selection();
+            }
+
+            if is_key_pressed(KeyCode::X) {
+                self.cut_selection();
+            }
+
+            if is_key_pressed(KeyCode::V) {
+                self.paste_clipboard();
+            }
+
+            if is_key_pressed(KeyCode::N) {
+                self.show_new_level = true;
+            }
+        }
+
+        if is_key_pressed(KeyCode::Escape) {
+            self.mode_manager.handle_mouse_cancel(&mut self.level);
         }
         
         
@@ -119,8 +169,11 @@ impl LevelEditor {
             let x = tile_x as usize;
             let y = tile_y as usize;
             
-            // Handle brush input while inside level
-            if is_mouse_button_pressed(MouseButton::Left) {
+            // Handle brush input while inside level; Alt+click is the eyedropper in every mode
+            let alt = is_key_down(KeyCode::LeftAlt) || is_key_down(KeyCode::RightAlt);
+            if is_mouse_button_pressed(MouseButton::Left) && alt {
+                self.pick_tile(x, y);
+            } else if is_mouse_button_pressed(MouseButton::Left) {
                 self.mode_manager.handle_mouse_press(&mut self.level, x, y);
             } else if is_mouse_button_down(MouseButton::Left) {
                 self.mode_manager.handle_mouse_drag(&mut self.level, x, y);
@@ -198,10 +251,13 @@ impl LevelEditor {
     }
 
     pub fn draw_level(&self) {
-        self.level.draw(&self.registry);
+        self.level.draw();
         // Draw selection indicator if a tile is selected
         if self.mode_manager.mode() == Mode::Selector {
-            self.level.draw_selection_indicator(self.get_selected_tile_coords());
+            match self.mode_manager.selection() {
+                Some(rect) if rect.0 != rect.2 || rect.1 != rect.3 => self.level.draw_selection_rect(rect),
+                _ => self.level.draw_selection_indicator(self.get_selected_tile_coords()),
+            }
         }
     }
 
@@ -224,7 +280,8 @@ impl LevelEditor {
 
     pub fn set_selected_tile(&mut self, tile: TileType) {
         let is_selector = self.mode_manager.mode() == Mode::Selector;
-        self.mode_manager.set_selected_tile(tile);
+        self.active_preset = None;
+        self.mode_manager.set_selected_tile(tile, self.level.registry());
         if is_selector {
             // Switch to drawing mode
             self.mode_manager.set_mode(Mode::Drawing);
@@ -248,6 +305,10 @@ impl LevelEditor {
         self.level.redo();
     }
 
+    pub fn end_merging_edits(&mut self) {
+        self.level.end_merging();
+    }
+
     pub fn can_undo(&self) -> bool {
         self.level.can_undo()
     }
@@ -256,6 +317,14 @@ impl LevelEditor {
         self.level.can_redo()
     }
 
+    pub fn undo_description(&self) -> Option<&str> {
+        self.level.undo_description()
+    }
+
+    pub fn redo_description(&self) -> Option<&str> {
+        self.level.redo_description()
+    }
+
     pub fn get_selected_tile_coords(&self) -> Option<(usize, usize)> {
         self.mode_manager.get_selected_tile_coords()
     }
@@ -270,45 +339,240 @@ impl LevelEditor {
 
     pub fn level_mut(&mut self) -> &mut Level { &mut self.level }
     pub fn level(&self) -> &Level { &self.level }
-    pub fn tile_type_registry(&self) -> &TileTypeRegistry { &self.tile_type_registry }
+    pub fn registry(&self) -> &TileRegistry { self.level.registry() }
 
     pub fn get_selected_platform_info(&self) -> Option<(TileType, usize, usize, usize, usize)> {
-        if let Some((x, y)) = self.get_selected_tile_coords() {
-            if let Some(platform) = self.level.platform_at(x, y) {
-                return Some((platform.tile_type.clone(), platform.min_x, platform.min_y, platform.max_x, platform.max_y));
-            }
+        if let Some((x, y)) = self.get_selected_tile_coords()
+            && let Some(platform) = self.level.platform_at(x, y) {
+            return Some((platform.tile_type.clone(), platform.min_x, platform.min_y, platform.max_x, platform.max_y));
         }
         None
     }
 
     pub fn update_selected_tile(&mut self, tile_type: TileType) {
         if let Some((x, y)) = self.get_selected_tile_coords() {
+            let registry = self.level.registry.clone();
             if let Some(tile) = self.level.get_tile_mut(x, y) {
-                tile.set_tile_type(tile_type);
+                tile.set_tile_type(tile_type, &registry);
             }
         }
     }
 
     pub fn update_selected_tile_name(&mut self, name: String) {
-        if let Some((x, y)) = self.get_selected_tile_coords() {
-            if let Some(tile) = self.level.get_tile_mut(x, y) {
-                tile.set_name(name);
+        if let Some((x, y)) = self.get_selected_tile_coords()
+            && let Some(tile) = self.level.get_tile_mut(x, y) {
+            tile.set_name(name);
+        }
+    }
+
+    // Brush used for plain tiles; structure kinds always use their own brush
+    pub fn paint_brush(&self) -> DrawingBrushType { self.mode_manager.paint_brush() }
+    pub fn set_paint_brush(&mut self, brush: DrawingBrushType) {
+        if self.mode_manager.mode() != Mode::Drawing { self.mode_manager.set_mode(Mode::Drawing); }
+        self.mode_manager.set_drawing_brush_type(brush);
+    }
+    pub fn fill_within_module(&self) -> bool { self.mode_manager.fill_within_module() }
+    pub fn set_fill_within_module(&mut self, within: bool) { self.mode_manager.set_fill_within_module(within); }
+    pub fn drawing_brush(&self) -> DrawingBrushType { self.mode_manager.drawing_brush_type() }
+    pub fn lock_platforms(&self) -> bool { self.mode_manager.lock_platforms() }
+    pub fn set_lock_platforms(&mut self, lock: bool) { self.mode_manager.set_lock_platforms(lock); }
+
+    pub fn pick_metadata(&self) -> bool { self.pick_metadata }
+    pub fn set_pick_metadata(&mut self, pick: bool) { self.pick_metadata = pick; }
+    pub fn has_stamp(&self) -> bool { self.mode_manager.stamp().is_some() }
+    pub fn clear_stamp(&mut self) { self.active_preset = None; self.mode_manager.clear_stamp(); }
+
+    // Eyedropper: select the kind under the cursor. Stairs cells pick the stairs structure; with
+    // pick_metadata a plain tile's fields are stamped onto later placements (minus its object id)
+    pub fn pick_tile(&mut self, x: usize, y: usize) {
+        let Some(tile) = self.level.get_tile(x, y) else { return };
+        let registry = self.level.registry.clone();
+        let on_stairs = self.level.stairs_map[y][x].is_some();
+        let tile_type = if on_stairs {
+            match registry.kinds().iter().find(|k| k.structure == Some(StructureKind::Stairs)) {
+                Some(kind) => TileType::Custom(kind.key.clone()),
+                None => return,
             }
+        } else {
+            tile.tile_type.clone()
+        };
+        let stamp = (self.pick_metadata && !on_stairs && tile.tile_type != TileType::Air).then(|| {
+            let mut fields = tile.metadata.clone();
+            set_id(&mut fields, String::new());
+            fields
+        });
+        self.active_preset = None;
+        self.mode_manager.pick_tile(tile_type, stamp, &registry);
+        if self.mode_manager.mode() == Mode::Selector {
+            self.mode_manager.set_mode(Mode::Drawing);
         }
     }
 
+    pub fn segmentation(&self) -> Segmentation { self.level.segmentation() }
+    pub fn set_segmentation(&mut self, segmentation: Segmentation) { self.level.set_segmentation(segmentation); }
+
+    // Object ids
+    pub fn auto_ids(&self) -> bool { self.level.auto_ids }
+    pub fn set_auto_ids(&mut self, auto: bool) {
+        if self.level.auto_ids != auto { self.level.mark_modified(); }
+        self.level.auto_ids = auto;
+    }
+    pub fn assign_missing_ids(&mut self) -> usize { self.level.assign_missing_ids() }
+
+    // Metadata presets: the active one is stamped onto tiles placed by the drawing brush
+    pub fn active_preset(&self) -> Option<usize> { self.active_preset }
+
+    pub fn use_preset(&mut self, index: Option<usize>) {
+        let fields = index.and_then(|i| self.level.presets().get(i)).map(|p| p.fields.clone());
+        self.active_preset = index.filter(|_| fields.is_some());
+        self.mode_manager.set_stamp(fields);
+    }
+
+    // Store the tile's current metadata as a preset of its kind
+    pub fn save_preset_from_tile(&mut self, x: usize, y: usize, name: &str) {
+        let Some(tile) = self.level.get_tile(x, y) else { return };
+        if tile.tile_type == TileType::Air || name.trim().is_empty() { return; }
+        let (tile_type, fields) = (tile.tile_type.clone(), tile.metadata.clone());
+        let index = self.level.save_preset(name.trim(), tile_type, fields);
+        if self.active_preset == Some(index) {
+            self.use_preset(Some(index));
+        }
+    }
+
+    pub fn remove_preset(&mut self, index: usize) {
+        self.level.remove_preset(index);
+        match self.active_preset {
+            Some(i) if i == index => self.clear_stamp(),
+            Some(i) if i > index => self.active_preset = Some(i - 1),
+            _ => {}
+        }
+    }
+
+    // Clipboard: copy/cut work on the selector's rectangle, paste floats the content until clicked
+    pub fn copy_selection(&mut self) {
+        if let Some(clip) = self.mode_manager.selection().and_then(|rect| self.level.copy_rect(rect)) {
+            self.clipboard = Some(clip);
+        }
+    }
+
+    pub fn cut_selection(&mut self) {
+        if let Some(rect) = self.mode_manager.selection()
+            && let Some(clip) = self.level.copy_rect(rect) {
+            self.clipboard = Some(clip);
+            self.level.cut_rect(rect);
+        }
+    }
+
+    pub fn paste_clipboard(&mut self) {
+        if let Some(clip) = self.clipboard.clone() {
+            self.mode_manager.start_paste(clip);
+        }
+    }
+
+    pub fn has_clipboard(&self) -> bool { self.clipboard.is_some() }
+    pub fn selection(&self) -> Option<CellRect> { self.mode_manager.selection() }
+
     // Modules helpers for UI
     pub fn toggle_modules_view(&mut self) { self.show_modules = !self.show_modules; }
     pub fn show_modules_view(&self) -> bool { self.show_modules }
     pub fn modules(&self) -> &Vec<usize> { self.level.modules() }
-    pub fn set_module_span(&mut self, idx: usize, span: usize) { if let Some(s) = self.level.modules_mut().get_mut(idx) { *s = span; } self.level.apply_modules_as_width(); }
-    pub fn add_module(&mut self, span: usize) { self.level.modules_mut().push(span.max(1)); self.level.apply_modules_as_width(); }
-    pub fn remove_module(&mut self, idx: usize) { if idx < self.level.modules_mut().len() { self.level.modules_mut().remove(idx); self.level.apply_modules_as_width(); } }
+    pub fn set_module_span(&mut self, idx: usize, span: usize) { self.level.set_module_span(idx, span); }
+    pub fn add_module(&mut self, span: usize) { self.level.insert_module(self.level.modules().len(), span); }
+    pub fn insert_module(&mut self, idx: usize, span: usize) { self.level.insert_module(idx, span); }
+    pub fn remove_module(&mut self, idx: usize) { self.level.delete_module(idx); }
+    pub fn move_module(&mut self, from: usize, to: usize) { self.level.move_module(from, to); }
+    pub fn duplicate_module(&mut self, idx: usize) { self.level.duplicate_module(idx); }
     pub fn level_width(&self) -> usize { self.level.width() }
+    pub fn level_height(&self) -> usize { self.level.height() }
+    pub fn set_level_height(&mut self, height: usize, edge: HeightEdge) { self.level.set_height(height, edge); }
+    pub fn level_theme(&self) -> &str { self.level.theme() }
+    pub fn set_level_theme(&mut self, theme: &str) { self.level.set_theme(theme); }
+
+    // Workspace: the tab methods live in workspace.rs
+    pub fn show_new_level(&self) -> bool { self.show_new_level }
+    pub fn set_show_new_level(&mut self, show: bool) { self.show_new_level = show; }
+
+    // Module library
+    pub fn toggle_library_view(&mut self) { self.show_library = !self.show_library; }
+    pub fn show_library_view(&self) -> bool { self.show_library }
+    pub fn library(&self) -> &ModuleLibrary { &self.library }
+    pub fn library_note(&self) -> Option<&str> { self.library_note.as_deref() }
+    pub fn reload_library(&mut self) { self.library.reload(); }
+    pub fn save_module_to_library(&mut self, idx: usize, name: &str, tags: &str) {
+        let tags = tags.split(',').map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect();
+        let Some(module) = self.level.module_to_library(idx, name, tags) else { return };
+        self.library_note = Some(match self.library.save(&module) {
+            Ok(()) => format!("Saved \"{}\"", module.name),
+            Err(e) => format!("Could not save \"{}\": {}", module.name, e),
+        });
+    }
+    pub fn delete_library_module(&mut self, entry: usize) {
+        let name = self.library.entries().get(entry).map(|e| e.module.name.clone()).unwrap_or_default();
+        self.library_note = self.library.delete(entry).err().map(|e| format!("Could not delete \"{}\": {}", name, e));
+    }
+    pub fn insert_library_module(&mut self, entry: usize, idx: usize) {
+        if let Some(e) = self.library.entries().get(entry) {
+            self.level.insert_library_module(idx, &e.module);
+        }
+    }
+    // Where a library module dropped at this screen position goes: before the module under the
+    // pointer if it lands on its left half, after it otherwise
+    pub fn module_insert_index_at(&self, screen_pos: Vec2) -> usize {
+        let x = self.camera.screen_to_world(screen_pos).x;
+        if x < 0.0 { return 0; }
+        match self.level.module_index_for_x(x.floor() as usize) {
+            Some(i) => {
+                let middle = self.level.module_start(i) as f32 + self.level.modules()[i] as f32 / 2.0;
+                if x < middle { i } else { i + 1 }
+            }
+            None => self.level.modules().len(),
+        }
+    }
 
     // Export
-    pub fn level_export_json(&self, name: String) -> serde_json::Result<String> { self.level.export_to_json(name, &self.registry) }
+    pub fn level_export_json(&self, name: String) -> serde_json::Result<String> { self.level.export_to_json(name) }
 
     // Import
-    pub fn level_import_json(&mut self, json: &str) -> serde_json::Result<()> { self.level.import_from_json(json, &self.registry) }
+    pub fn strict_import(&self) -> bool { self.strict_import }
+    pub fn set_strict_import(&mut self, strict: bool) { self.strict_import = strict; }
+    pub fn import_outcome(&self) -> Option<&Result<ImportReport, ImportError>> { self.import_outcome.as_ref() }
+    pub fn set_import_outcome(&mut self, outcome: Result<ImportReport, ImportError>) { self.import_outcome = Some(outcome); }
+    pub fn dismiss_import_outcome(&mut self) { self.import_outcome = None; }
+
+    // Validation
+    pub fn run_validation(&mut self) {
+        self.diagnostics = self.level.validate();
+        self.validation_note = None;
+        self.show_validation = true;
+    }
+    pub fn diagnostics(&self) -> &[Diagnostic] { &self.diagnostics }
+    pub fn show_validation(&self) -> bool { self.show_validation }
+    pub fn set_show_validation(&mut self, show: bool) { self.show_validation = show; }
+    pub fn validation_note(&self) -> Option<&str> { self.validation_note.as_deref() }
+
+    // Validate before exporting: errors block the export, warnings are only listed
+    pub fn validate_for_export(&mut self) -> bool {
+        self.run_validation();
+        let errors = self.diagnostics.iter().filter(|d| d.severity == Severity::Error).count();
+        if errors > 0 {
+            self.validation_note = Some(format!("Export blocked: {} error(s) must be fixed first", errors));
+            return false;
+        }
+        if self.diagnostics.is_empty() {
+            self.show_validation = false;
+        }
+        true
+    }
+
+    // Center the camera on a cell and select it
+    pub fn focus_cell(&mut self, x: usize, y: usize) {
+        self.camera.set_target(vec2(x as f32 + 0.5, y as f32 + 0.5));
+        self.mode_manager.select_cell(x, y);
+    }
+
+    // Native project save; opening one goes through the workspace
+    pub fn project_save_json(&self) -> serde_json::Result<String> { self.level.save_project() }
+    pub fn project_error(&self) -> Option<&str> { self.project_error.as_deref() }
+    pub fn set_project_error(&mut self, error: String) { self.project_error = Some(error); }
+    pub fn dismiss_project_error(&mut self) { self.project_error = None; }
 } 
\ No newline at end of file
@@ -1,5 +1,7 @@
-use crate::tile::TileType;
-use crate::level::Level;
+use std::rc::Rc;
+use crate::tile::{TileType, TileRegistry};
+use crate::tile_type_system::MetaField;
+use crate::level::{Level, Clip, CellRect};
 use super::modes::{Mode, ModeTrait, DrawingMode, SelectorMode, DrawingBrushType};
 
 // Mode manager that handles the current mode and selected tile
@@ -23,14 +25,16 @@ impl ModeManager {
     pub fn set_mode(&mut self, mode: Mode) {
         self.current_mode = mode;
         // Cancel any active operations when switching modes
-        self.drawing_mode.on_mouse_cancel(&mut Level::new(0, 0)); // Dummy level
+        self.drawing_mode.on_mouse_cancel(&mut Level::new(0, 0, Rc::default())); // Dummy level
         self.selector_mode.clear_selection();
     }
 
-    pub fn set_selected_tile(&mut self, tile: TileType) {
+    pub fn set_selected_tile(&mut self, tile: TileType, registry: &TileRegistry) {
+        // A newly chosen tile starts from its default metadata again
+        self.drawing_mode.set_stamp(None);
         // Auto-detect brush type when selecting a tile in drawing mode
         if self.current_mode == Mode::Drawing {
-            self.drawing_mode.auto_detect_brush_type(&tile);
+            self.drawing_mode.auto_detect_brush_type(&tile, registry);
         }
         self.selected_tile = tile;
     }
@@ -85,6 +89,22 @@ impl ModeManager {
         }
     }
 
+    pub fn selection(&self) -> Option<CellRect> {
+        if self.current_mode == Mode::Selector { self.selector_mode.selection() } else { None }
+    }
+
+    // Float clipboard content in the selector until the user clicks where it should go
+    pub fn start_paste(&mut self, clip: Clip) {
+        self.set_mode(Mode::Selector);
+        self.selector_mode.start_paste(clip);
+    }
+
+    // Switch to the selector and select a cell, e.g. when jumping to a validation problem
+    pub fn select_cell(&mut self, x: usize, y: usize) {
+        self.set_mode(Mode::Selector);
+        self.selector_mode.select(x, y);
+    }
+
     pub fn update_highlights(&mut self, level: &mut Level, mouse_x: Option<usize>, mouse_y: Option<usize>) {
         let mode = self.get_current_mode();
         mode.update_highlights(level, mouse_x, mouse_y);
@@ -101,6 +121,44 @@ impl ModeManager {
         self.drawing_mode.brush_type()
     }
 
+    pub fn paint_brush(&self) -> DrawingBrushType {
+        self.drawing_mode.paint_brush()
+    }
+
+    // Eyedropper result: the picked tile becomes the selection, optionally with its metadata as a stamp
+    pub fn pick_tile(&mut self, tile: TileType, stamp: Option<Vec<MetaField>>, registry: &TileRegistry) {
+        self.set_selected_tile(tile, registry);
+        self.drawing_mode.set_stamp(stamp);
+    }
+
+    pub fn stamp(&self) -> Option<&[MetaField]> {
+        self.drawing_mode.stamp()
+    }
+
+    pub fn set_stamp(&mut self, stamp: Option<Vec<MetaField>>) {
+        self.drawing_mode.set_stamp(stamp);
+    }
+
+    pub fn clear_stamp(&mut self) {
+        self.drawing_mode.set_stamp(None);
+    }
+
+    pub fn lock_platforms(&self) -> bool {
+        self.drawing_mode.lock_platforms()
+    }
+
+    pub fn set_lock_platforms(&mut self, lock: bool) {
+        self.drawing_mode.set_lock_platforms(lock);
+    }
+
+    pub fn fill_within_module(&self) -> bool {
+        self.drawing_mode.fill_within_module()
+    }
+
+    pub fn set_fill_within_module(&mut self, within: bool) {
+        self.drawing_mode.set_fill_within_module(within);
+    }
+
     pub fn mode(&self) -> Mode {
         self.current_mode
     }
@@ -1,5 +1,7 @@
-use crate::tile::TileType;
-use crate::level::Level;
+use crate::tile::{TileType, TileRegistry, Stairs, StairsOrientation};
+use crate::tile_type_system::MetaField;
+use crate::tile_manifest::StructureKind;
+use crate::level::{Level, Clip, CellRect};
 use egui_macroquad::macroquad::prelude::*;
 
 // High-level modes that the user can select
@@ -22,6 +24,9 @@ impl Mode {
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum DrawingBrushType {
     Single,      // Single tile placement
+    Fill,        // Flood fill of the clicked region
+    Line,        // Straight line between press and release
+    Outline,     // Hollow rectangle between press and release
     Platform,    // Rectangle platform
     Stairs,      // Stairs structure
 }
@@ -30,10 +35,16 @@ impl DrawingBrushType {
     pub fn name(&self) -> &'static str {
         match self {
             DrawingBrushType::Single => "Single",
+            DrawingBrushType::Fill => "Fill",
+            DrawingBrushType::Line => "Line",
+            DrawingBrushType::Outline => "Outline",
             DrawingBrushType::Platform => "Platform", 
             DrawingBrushType::Stairs => "Stairs",
         }
     }
+
+    // Brushes the user can pick for plain tiles; structure brushes follow the selected tile
+    pub const PAINT_BRUSHES: [DrawingBrushType; 4] = [DrawingBrushType::Single, DrawingBrushType::Fill, DrawingBrushType::Line, DrawingBrushType::Outline];
 }
 
 // Trait for modes that can act on a level
@@ -55,6 +66,10 @@ pub struct DrawingMode {
     start_pos: Option<(usize, usize)>,
     end_pos: Option<(usize, usize)>,
     brush_type: DrawingBrushType,
+    paint_brush: DrawingBrushType, // Last brush picked for plain tiles
+    fill_within_module: bool,
+    stamp: Option<Vec<MetaField>>, // Metadata picked with the eyedropper, applied to placed tiles
+    lock_platforms: bool, // Platform brush rectangles stay as drawn instead of being re-segmented
 }
 
 impl DrawingMode {
@@ -64,36 +79,79 @@ impl DrawingMode {
             start_pos: None,
             end_pos: None,
             brush_type: DrawingBrushType::Single,
+            paint_brush: DrawingBrushType::Single,
+            fill_within_module: false,
+            stamp: None,
+            lock_platforms: false,
         }
     }
 
+    pub fn lock_platforms(&self) -> bool {
+        self.lock_platforms
+    }
+
+    pub fn set_lock_platforms(&mut self, lock: bool) {
+        self.lock_platforms = lock;
+    }
+
+    pub fn stamp(&self) -> Option<&[MetaField]> {
+        self.stamp.as_deref()
+    }
+
+    pub fn set_stamp(&mut self, stamp: Option<Vec<MetaField>>) {
+        self.stamp = stamp;
+    }
+
     pub fn set_brush_type(&mut self, brush_type: DrawingBrushType) {
         self.brush_type = brush_type;
+        if DrawingBrushType::PAINT_BRUSHES.contains(&brush_type) { self.paint_brush = brush_type; }
+    }
+
+    pub fn paint_brush(&self) -> DrawingBrushType {
+        self.paint_brush
+    }
+
+    pub fn fill_within_module(&self) -> bool {
+        self.fill_within_module
+    }
+
+    pub fn set_fill_within_module(&mut self, within: bool) {
+        self.fill_within_module = within;
     }
 
     pub fn brush_type(&self) -> DrawingBrushType {
         self.brush_type
     }
 
-    // Auto-detect brush type based on selected tile
-    pub fn auto_detect_brush_type(&mut self, tile: &TileType) {
-        match tile {
-            TileType::Custom(name) => {
-                match name.as_str() {
-                    "stairs" => self.brush_type = DrawingBrushType::Stairs,
-                    "platform" => self.brush_type = DrawingBrushType::Platform,
-                    _ => self.brush_type = DrawingBrushType::Single,
-                }
-            }
-            TileType::Air => self.brush_type = DrawingBrushType::Single,
-        }
+    // Auto-detect brush type based on the selected tile's structure kind
+    pub fn auto_detect_brush_type(&mut self, tile: &TileType, registry: &TileRegistry) {
+        self.brush_type = match registry.structure_for(tile) {
+            Some(StructureKind::Stairs) => DrawingBrushType::Stairs,
+            Some(StructureKind::Platform) => DrawingBrushType::Platform,
+            None => self.paint_brush,
+        };
     }
 
     fn shape(&self, start: (usize, usize), end: (usize, usize)) -> Vec<(usize, usize)> {
         match self.brush_type {
-            DrawingBrushType::Single => {
+            DrawingBrushType::Single | DrawingBrushType::Fill => {
                 vec![(start.0, start.1)]
             }
+            DrawingBrushType::Line => line_cells(start, end),
+            DrawingBrushType::Outline => {
+                let (min_x, min_y) = (start.0.min(end.0), start.1.min(end.1));
+                let (max_x, max_y) = (start.0.max(end.0), start.1.max(end.1));
+                let mut res = vec![];
+                for x in min_x..=max_x {
+                    res.push((x, min_y));
+                    if max_y != min_y { res.push((x, max_y)); }
+                }
+                for y in min_y + 1..max_y {
+                    res.push((min_x, y));
+                    if max_x != min_x { res.push((max_x, y)); }
+                }
+                res
+            }
             DrawingBrushType::Platform => {
                 // Rectangle platform
                 let start_x = start.0.min(end.0);
@@ -110,34 +168,8 @@ impl DrawingMode {
                 res
             }
             DrawingBrushType::Stairs => {
-                // Stairs - create proper stairs pattern
-                let mut res = Vec::new();
-                let (sx, sy) = start;
-                let (ex, ey) = end;
-                let dx = sx.abs_diff(ex);
-                let dy = sy.abs_diff(ey);
-                let steps = dx.min(dy); // Force stairs to be square
-                
-                for i in 0..=steps {
-                    let (start_pos, end_y) = if sy < ey {
-                        if sx < ex {
-                            ((sx + i, sy + i), ey)
-                        } else {
-                            ((sx - i, sy + i), ey)
-                        }
-                    } else {
-                        if sx < ex {
-                            ((sx + i, sy - i), sy)
-                        } else {
-                            ((sx - i, sy - i), sy)
-                        }
-                    };
-                    
-                    for y in start_pos.1..=end_y {
-                        res.push((start_pos.0, y));
-                    }
-                }
-                res
+                let (x, y, size, orientation) = stairs_from_drag(start, end);
+                Stairs::new(TileType::Air, x, y, size, orientation, Vec::new()).cells()
             }
         }
     }
@@ -150,17 +182,21 @@ impl ModeTrait for DrawingMode {
 
     fn on_mouse_press(&mut self, level: &mut Level, x: usize, y: usize, tile: TileType) -> bool {
         // Auto-detect brush type based on tile
-        self.auto_detect_brush_type(&tile);
+        self.auto_detect_brush_type(&tile, level.registry());
         
         self.active = true;
         self.start_pos = Some((x, y));
+        self.end_pos = None;
         
         match self.brush_type {
             DrawingBrushType::Single => {
-                level.set_tile(x, y, tile);
+                level.set_tile_stamped(x, y, tile, self.stamp.as_deref());
             }
-            DrawingBrushType::Platform | DrawingBrushType::Stairs => {
-                // For structures, we'll place on mouse release
+            DrawingBrushType::Fill => {
+                level.flood_fill(x, y, tile, self.fill_within_module, self.stamp.as_deref());
+            }
+            DrawingBrushType::Line | DrawingBrushType::Outline | DrawingBrushType::Platform | DrawingBrushType::Stairs => {
+                // For shapes and structures, we'll place on mouse release
                 level.set_highlighted_tiles(vec![(x, y)]);
             }
         }
@@ -174,9 +210,10 @@ impl ModeTrait for DrawingMode {
 
         match self.brush_type {
             DrawingBrushType::Single => {
-                level.set_tile(x, y, tile);
+                level.set_tile_stamped(x, y, tile, self.stamp.as_deref());
             }
-            DrawingBrushType::Platform | DrawingBrushType::Stairs => {
+            DrawingBrushType::Fill => {}
+            DrawingBrushType::Line | DrawingBrushType::Outline | DrawingBrushType::Platform | DrawingBrushType::Stairs => {
                 self.end_pos = Some((x, y));
                 if let Some(start) = self.start_pos {
                     let shape = self.shape(start, (x, y));
@@ -187,15 +224,26 @@ impl ModeTrait for DrawingMode {
         true
     }
 
-    fn on_mouse_release(&mut self, level: &mut Level, _tile: TileType) -> bool {
+    fn on_mouse_release(&mut self, level: &mut Level, tile: TileType) -> bool {
         if !self.active {
             return false;
         }
 
         match self.brush_type {
-            DrawingBrushType::Single => {
+            DrawingBrushType::Single | DrawingBrushType::Fill => {
                 level.finish_operation();
             }
+            DrawingBrushType::Line | DrawingBrushType::Outline => {
+                // A click without dragging places a single cell
+                if let Some(start) = self.start_pos {
+                    let shape = self.shape(start, self.end_pos.unwrap_or(start));
+                    level.begin_operation(if self.brush_type == DrawingBrushType::Line { "Draw Line" } else { "Draw Outline" });
+                    for (x, y) in shape {
+                        level.set_tile_stamped(x, y, tile.clone(), self.stamp.as_deref());
+                    }
+                    level.finish_operation();
+                }
+            }
             DrawingBrushType::Platform | DrawingBrushType::Stairs => {
                 if let (Some(start), Some(end)) = (self.start_pos, self.end_pos) {
                     let shape = self.shape(start, end);
@@ -217,14 +265,15 @@ impl ModeTrait for DrawingMode {
                     
                     // Create the appropriate structure
                     match self.brush_type {
-                        DrawingBrushType::Platform => {
-                            // Platforms are automatically detected by rebuild_platforms
-                            // No additional action needed
+                        // Platforms are automatically detected by rebuild_platforms unless the rectangle is locked
+                        DrawingBrushType::Platform if self.lock_platforms => {
+                            let (min_x, min_y) = (start.0.min(end.0), start.1.min(end.1));
+                            let (max_x, max_y) = (start.0.max(end.0), start.1.max(end.1));
+                            level.lock_platform_rect(wall_tile.clone(), (min_x, min_y, max_x, max_y));
                         }
                         DrawingBrushType::Stairs => {
-                            // Calculate orientation for stairs
-                            let orientation = self.stairs_orientation(start, end);
-                            level.assign_stairs_with_cells(wall_tile, &shape, orientation);
+                            let (x, y, size, orientation) = stairs_from_drag(start, end);
+                            level.place_stairs(wall_tile, x, y, size, orientation);
                         }
                         _ => {}
                     }
@@ -273,10 +322,10 @@ impl ModeTrait for DrawingMode {
             if let (Some(x), Some(y)) = (mouse_x, mouse_y) {
                 if x < level.width() && y < level.height() {
                     match self.brush_type {
-                        DrawingBrushType::Single => {
+                        DrawingBrushType::Single | DrawingBrushType::Fill => {
                             level.set_highlighted_tiles(vec![(x, y)]);
                         }
-                        DrawingBrushType::Platform | DrawingBrushType::Stairs => {
+                        DrawingBrushType::Line | DrawingBrushType::Outline | DrawingBrushType::Platform | DrawingBrushType::Stairs => {
                             if let Some(start) = self.start_pos {
                                 let shape = self.shape(start, (x, y));
                                 level.set_highlighted_tiles(shape);
@@ -295,6 +344,26 @@ impl ModeTrait for DrawingMode {
     }
 }
 
+// Bresenham line between two cells, endpoints included
+fn line_cells(start: (usize, usize), end: (usize, usize)) -> Vec<(usize, usize)> {
+    let (mut x, mut y) = (start.0 as i64, start.1 as i64);
+    let (ex, ey) = (end.0 as i64, end.1 as i64);
+    let dx = (ex - x).abs();
+    let dy = -(ey - y).abs();
+    let sx = if x < ex { 1 } else { -1 };
+    let sy = if y < ey { 1 } else { -1 };
+    let mut err = dx + dy;
+    let mut res = vec![];
+    loop {
+        res.push((x as usize, y as usize));
+        if x == ex && y == ey { break; }
+        let e2 = 2 * err;
+        if e2 >= dy { err += dy; x += sx; }
+        if e2 <= dx { err += dx; y += sy; }
+    }
+    res
+}
+
 impl DrawingMode {
     fn crosses_module_border(&self, level: &Level, cells: &[(usize, usize)]) -> bool {
         if cells.is_empty() { return false; }
@@ -306,35 +375,88 @@ impl DrawingMode {
         }
         false
     }
+}
 
-    fn stairs_orientation(&self, start: (usize, usize), end: (usize, usize)) -> i32 {
-        if start.0 < end.0 && start.1 < end.1 {
-            return -1; // Inverted: was 1
-        }
-        else if start.0 > end.0 && start.1 > end.1 {
-            return -1; // Inverted: was 1
-        }
-        return 1; // Inverted: was -1
-    }
+// Stairs spanned by a drag: (x, y, size, orientation) of the square box, grown from the start
+// cell toward the end by the shorter of the two distances
+fn stairs_from_drag(start: (usize, usize), end: (usize, usize)) -> (usize, usize, usize, StairsOrientation) {
+    let steps = start.0.abs_diff(end.0).min(start.1.abs_diff(end.1));
+    let x = if start.0 <= end.0 { start.0 } else { start.0 - steps };
+    let y = if start.1 <= end.1 { start.1 } else { start.1 - steps };
+    let orientation = if (start.0 < end.0) == (start.1 < end.1) { StairsOrientation::AscendingLeft } else { StairsOrientation::AscendingRight };
+    (x, y, steps + 1, orientation)
+}
+
+// What a left-button drag in the selector is doing
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum SelectorDrag {
+    Marquee,                                  // Spanning a new selection from the anchor
+    Move { grab: (usize, usize), to: (usize, usize) }, // Dragging the selection's content
 }
 
 // Selector mode implementation
 pub struct SelectorMode {
-    selected_tile: Option<(usize, usize)>,
+    selected_tile: Option<(usize, usize)>, // Anchor cell, shown by the inspector
+    selection: Option<CellRect>,
+    drag: Option<SelectorDrag>,
+    paste_preview: Option<Clip>, // Floating clipboard content waiting to be placed
 }
 
 impl SelectorMode {
     pub fn new() -> Self {
-        Self { selected_tile: None }
+        Self { selected_tile: None, selection: None, drag: None, paste_preview: None }
     }
 
     pub fn get_selected_tile(&self) -> Option<(usize, usize)> {
         self.selected_tile
     }
 
+    pub fn selection(&self) -> Option<CellRect> {
+        self.selection
+    }
+
     pub fn clear_selection(&mut self) {
         self.selected_tile = None;
+        self.selection = None;
+        self.drag = None;
+        self.paste_preview = None;
+    }
+
+    pub fn select(&mut self, x: usize, y: usize) {
+        self.selected_tile = Some((x, y));
+        self.selection = Some((x, y, x, y));
     }
+
+    pub fn start_paste(&mut self, clip: Clip) {
+        self.drag = None;
+        self.paste_preview = Some(clip);
+    }
+
+    fn select_rect(&mut self, rect: CellRect) {
+        self.selected_tile = Some((rect.0, rect.1));
+        self.selection = Some(rect);
+    }
+
+    // Where the dragged selection would land, clamped so it never starts left of / above the level
+    fn move_target(rect: CellRect, grab: (usize, usize), to: (usize, usize)) -> (usize, usize) {
+        let x = (rect.0 + to.0).saturating_sub(grab.0);
+        let y = (rect.1 + to.1).saturating_sub(grab.1);
+        (x, y)
+    }
+}
+
+fn rect_from(a: (usize, usize), b: (usize, usize)) -> CellRect {
+    (a.0.min(b.0), a.1.min(b.1), a.0.max(b.0), a.1.max(b.1))
+}
+
+fn rect_cells(rect: CellRect) -> Vec<(usize, usize)> {
+    let mut cells = Vec::new();
+    for y in rect.1..=rect.3 { for x in rect.0..=rect.2 { cells.push((x, y)); } }
+    cells
+}
+
+fn rect_contains(rect: CellRect, x: usize, y: usize) -> bool {
+    x >= rect.0 && x <= rect.2 && y >= rect.1 && y <= rect.3
 }
 
 impl ModeTrait for SelectorMode {
@@ -342,18 +464,53 @@ impl ModeTrait for SelectorMode {
         "Selector"
     }
 
-    fn on_mouse_press(&mut self, _level: &mut Level, x: usize, y: usize, _tile: TileType) -> bool {
-        self.selected_tile = Some((x, y));
+    fn on_mouse_press(&mut self, level: &mut Level, x: usize, y: usize, _tile: TileType) -> bool {
+        if let Some(clip) = self.paste_preview.take() {
+            if level.paste_clip(&clip, x, y) {
+                self.select_rect((x, y, x + clip.width - 1, y + clip.height - 1));
+            } else {
+                // Keep floating so the user can try another spot
+                self.paste_preview = Some(clip);
+            }
+            return true;
+        }
+
+        match self.selection {
+            Some(rect) if rect_contains(rect, x, y) => {
+                self.drag = Some(SelectorDrag::Move { grab: (x, y), to: (x, y) });
+            }
+            _ => {
+                self.select(x, y);
+                self.drag = Some(SelectorDrag::Marquee);
+            }
+        }
         true
     }
 
-    fn on_mouse_drag(&mut self, _level: &mut Level, _x: usize, _y: usize, _tile: TileType) -> bool {
-        // Selector doesn't do anything on drag
+    fn on_mouse_drag(&mut self, _level: &mut Level, x: usize, y: usize, _tile: TileType) -> bool {
+        match self.drag {
+            Some(SelectorDrag::Marquee) => {
+                if let Some(anchor) = self.selected_tile { self.selection = Some(rect_from(anchor, (x, y))); }
+            }
+            Some(SelectorDrag::Move { grab, .. }) => {
+                self.drag = Some(SelectorDrag::Move { grab, to: (x, y) });
+            }
+            None => {}
+        }
         true
     }
 
-    fn on_mouse_release(&mut self, _level: &mut Level, _tile: TileType) -> bool {
-        // Selector doesn't do anything on release
+    fn on_mouse_release(&mut self, level: &mut Level, _tile: TileType) -> bool {
+        if let (Some(SelectorDrag::Move { grab, to }), Some(rect)) = (self.drag, self.selection) {
+            let (tx, ty) = Self::move_target(rect, grab, to);
+            if grab == to {
+                // A plain click inside the selection picks that single cell
+                self.select(grab.0, grab.1);
+            } else if level.move_rect(rect, tx, ty) {
+                self.select_rect((tx, ty, tx + rect.2 - rect.0, ty + rect.3 - rect.1));
+            }
+        }
+        self.drag = None;
         true
     }
 
@@ -370,21 +527,38 @@ impl ModeTrait for SelectorMode {
     }
 
     fn draw_preview(&self, _level: &Level) {
-        // Selector doesn't need preview
+        // Previews are drawn through highlights
     }
 
     fn is_active(&self) -> bool {
-        self.selected_tile.is_some()
+        self.selected_tile.is_some() || self.paste_preview.is_some()
     }
 
     fn update_highlights(&mut self, level: &mut Level, mouse_x: Option<usize>, mouse_y: Option<usize>) {
-        // Selector highlights selected tile if one is selected, otherwise highlights mouse position
-        if let Some((selected_x, selected_y)) = self.selected_tile {
-            if selected_x < level.width() && selected_y < level.height() {
-                level.set_highlighted_tiles(vec![(selected_x, selected_y)]);
-            } else {
-                level.clear_highlights();
-            }
+        let in_level = |(x, y): &(usize, usize)| *x < level.width() && *y < level.height();
+
+        // Floating paste follows the mouse; nothing is shown where it can't be placed
+        if let Some(clip) = &self.paste_preview {
+            let cells = match (mouse_x, mouse_y) {
+                (Some(x), Some(y)) if level.can_place_clip(clip, x, y) => clip.cells_at(x, y),
+                _ => Vec::new(),
+            };
+            level.set_highlighted_tiles(cells);
+            return;
+        }
+
+        if let (Some(SelectorDrag::Move { grab, to }), Some(rect)) = (self.drag, self.selection) {
+            let (tx, ty) = Self::move_target(rect, grab, to);
+            let moved = (tx, ty, tx + rect.2 - rect.0, ty + rect.3 - rect.1);
+            let cells: Vec<(usize, usize)> = rect_cells(moved).into_iter().filter(in_level).collect();
+            level.set_highlighted_tiles(cells);
+            return;
+        }
+
+        // Selector highlights the selection if there is one, otherwise highlights mouse position
+        if let Some(rect) = self.selection {
+            let cells: Vec<(usize, usize)> = rect_cells(rect).into_iter().filter(in_level).collect();
+            level.set_highlighted_tiles(cells);
         } else if let (Some(x), Some(y)) = (mouse_x, mouse_y) {
             if x < level.width() && y < level.height() {
                 level.set_highlighted_tiles(vec![(x, y)]);
@@ -0,0 +1,145 @@
+use std::path::{Path, PathBuf};
+use crate::camera::Camera;
+use crate::level::{Level, ImportReport, ImportError, DEFAULT_THEME};
+use crate::editor::LevelEditor;
+
+// What the New Level dialog builds a level from
+#[derive(Clone, Debug, PartialEq)]
+pub struct NewLevelSettings {
+    pub name: String,
+    pub modules: Vec<usize>, // Span of each module, left to right
+    pub height: usize,
+    pub theme: String,
+}
+
+impl Default for NewLevelSettings {
+    fn default() -> Self {
+        Self { name: "untitled".to_string(), modules: vec![15, 15], height: 15, theme: DEFAULT_THEME.to_string() }
+    }
+}
+
+// One open level. The active tab's level and camera live in the editor itself so every editor
+// method works on them directly; the other tabs keep theirs parked here until switched to
+pub(super) struct LevelTab {
+    pub(super) name: String,
+    pub(super) path: Option<PathBuf>, // Project file it was opened from or last saved to
+    pub(super) parked: Option<(Level, Camera)>,
+}
+
+impl LevelTab {
+    pub(super) fn new(name: &str, path: Option<PathBuf>) -> LevelTab {
+        LevelTab { name: name.to_string(), path, parked: None }
+    }
+}
+
+impl LevelEditor {
+    pub fn tab_count(&self) -> usize { self.tabs.len() }
+    pub fn active_tab(&self) -> usize { self.active_tab }
+    pub fn tab_name(&self, index: usize) -> &str { &self.tabs[index].name }
+    pub fn tab_path(&self) -> Option<&Path> { self.tabs[self.active_tab].path.as_deref() }
+
+    pub fn tab_is_dirty(&self, index: usize) -> bool {
+        match &self.tabs[index].parked {
+            Some((level, _)) => level.is_dirty(),
+            None => self.level.is_dirty(),
+        }
+    }
+
+    pub fn new_level(&mut self, settings: &NewLevelSettings) {
+        let level = Level::with_modules(&settings.modules, settings.height, &settings.theme, self.level.registry.clone());
+        let name = settings.name.trim();
+        self.open_tab(level, if name.is_empty() { "untitled" } else { name }, None, false);
+    }
+
+    // Opening a project or game file gets its own tab. A blank tab that was never touched is
+    // replaced instead, so the level the editor starts with doesn't pile up
+    pub fn project_load_json(&mut self, json: &str, path: &Path) -> serde_json::Result<()> {
+        let mut level = Level::new(0, 0, self.level.registry.clone());
+        level.load_project(json)?;
+        self.open_tab(level, &file_name(path), Some(path.to_path_buf()), true);
+        Ok(())
+    }
+
+    pub fn level_import_json(&mut self, json: &str, path: &Path) -> Result<ImportReport, ImportError> {
+        let mut level = Level::new(0, self.level.height(), self.level.registry.clone());
+        let report = level.import_from_json(json, self.strict_import)?;
+        self.open_tab(level, &file_name(path), None, true);
+        Ok(report)
+    }
+
+    pub fn project_saved(&mut self, path: &Path) {
+        self.level.mark_saved();
+        let tab = &mut self.tabs[self.active_tab];
+        tab.name = file_name(path);
+        tab.path = Some(path.to_path_buf());
+    }
+
+    fn open_tab(&mut self, level: Level, name: &str, path: Option<PathBuf>, reuse_blank: bool) {
+        let blank = self.tabs[self.active_tab].path.is_none() && !self.level.is_dirty() && !self.level.can_undo();
+        let camera = Camera::new(level.width() as f32, level.height() as f32);
+        self.mode_manager.handle_mouse_cancel(&mut self.level);
+        self.level.finish_operation();
+        let level = std::mem::replace(&mut self.level, level);
+        let camera = std::mem::replace(&mut self.camera, camera);
+        if reuse_blank && blank {
+            self.tabs[self.active_tab] = LevelTab::new(name, path);
+        } else {
+            self.tabs[self.active_tab].parked = Some((level, camera));
+            self.tabs.push(LevelTab::new(name, path));
+            self.active_tab = self.tabs.len() - 1;
+        }
+        self.reset_level_state();
+    }
+
+    pub fn switch_tab(&mut self, index: usize) {
+        if index >= self.tabs.len() || index == self.active_tab { return; }
+        self.mode_manager.handle_mouse_cancel(&mut self.level);
+        self.level.finish_operation();
+        if let Some((level, camera)) = self.tabs[index].parked.take() {
+            let level = std::mem::replace(&mut self.level, level);
+            let camera = std::mem::replace(&mut self.camera, camera);
+            self.tabs[self.active_tab].parked = Some((level, camera));
+        }
+        self.active_tab = index;
+        self.reset_level_state();
+    }
+
+    // Closing a tab with unsaved changes asks first; see confirm_close_tab
+    pub fn close_tab(&mut self, index: usize) {
+        if index >= self.tabs.len() || self.tabs.len() == 1 || self.closing_tab.is_some() { return; }
+        if self.tab_is_dirty(index) {
+            self.closing_tab = Some(index);
+        } else {
+            self.remove_tab(index);
+        }
+    }
+
+    pub fn closing_tab(&self) -> Option<usize> { self.closing_tab }
+
+    pub fn confirm_close_tab(&mut self, close: bool) {
+        if let Some(index) = self.closing_tab.take() && close { self.remove_tab(index); }
+    }
+
+    fn remove_tab(&mut self, index: usize) {
+        if index == self.active_tab {
+            self.switch_tab(if index + 1 < self.tabs.len() { index + 1 } else { index - 1 });
+        }
+        self.tabs.remove(index);
+        if self.active_tab > index { self.active_tab -= 1; }
+    }
+
+    // Selection, validation results and the import report describe the level they came from;
+    // the clipboard is kept so content can be copied from one level and pasted into another
+    fn reset_level_state(&mut self) {
+        self.mode_manager.set_mode(self.mode_manager.mode());
+        self.clear_stamp();
+        self.diagnostics.clear();
+        self.show_validation = false;
+        self.validation_note = None;
+        self.import_outcome = None;
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_else(|| "untitled".to_string())
+}
@@ -0,0 +1,163 @@
+use serde::{Serialize, Deserialize};
+use crate::tile::{Tile, Platform, Stairs};
+use crate::level::Level;
+
+// Inclusive cell rectangle: (min_x, min_y, max_x, max_y)
+pub type CellRect = (usize, usize, usize, usize);
+
+// Level content copied out of a rectangle. Positions are relative to the rectangle's top-left
+// cell; only structures lying fully inside the rectangle are carried along
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Clip {
+    pub width: usize,
+    pub height: usize,
+    pub tiles: Vec<Vec<Tile>>,
+    pub platforms: Vec<Platform>,
+    pub stairs: Vec<(Stairs, Vec<(usize, usize)>)>, // Each stairs with the cells it covers
+}
+
+impl Clip {
+    // Cells the clip would cover with its top-left corner at (x, y)
+    pub fn cells_at(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        let mut cells = Vec::with_capacity(self.width * self.height);
+        for dy in 0..self.height { for dx in 0..self.width { cells.push((x + dx, y + dy)); } }
+        cells
+    }
+
+    // The clip without its top `rows` rows; structures reaching into them are dropped
+    pub fn without_top_rows(&self, rows: usize) -> Clip {
+        let rows = rows.min(self.height);
+        Clip {
+            width: self.width,
+            height: self.height - rows,
+            tiles: self.tiles[rows..].to_vec(),
+            platforms: self.platforms.iter()
+                .filter(|p| p.min_y >= rows)
+                .map(|p| Platform { min_y: p.min_y - rows, max_y: p.max_y - rows, ..p.clone() })
+                .collect(),
+            stairs: self.stairs.iter()
+                .filter(|(s, _)| s.min_y >= rows)
+                .map(|(s, cells)| (Stairs { min_y: s.min_y - rows, max_y: s.max_y - rows, ..s.clone() }, cells.iter().map(|&(x, y)| (x, y - rows)).collect()))
+                .collect(),
+        }
+    }
+}
+
+fn inside(rect: CellRect, min_x: usize, min_y: usize, max_x: usize, max_y: usize) -> bool {
+    min_x >= rect.0 && min_y >= rect.1 && max_x <= rect.2 && max_y <= rect.3
+}
+
+impl Level {
+    // A selection can outlive an undo that shrank the level; keep only the part still inside
+    fn clamp_rect(&self, rect: CellRect) -> Option<CellRect> {
+        if rect.0 >= self.width || rect.1 >= self.height { return None; }
+        Some((rect.0, rect.1, rect.2.min(self.width - 1), rect.3.min(self.height - 1)))
+    }
+
+    pub fn copy_rect(&self, rect: CellRect) -> Option<Clip> {
+        let (min_x, min_y, max_x, max_y) = self.clamp_rect(rect)?;
+        let rect = (min_x, min_y, max_x, max_y);
+        let tiles = (min_y..=max_y).map(|y| self.tiles[y][min_x..=max_x].to_vec()).collect();
+
+        let platforms = self.platforms.iter()
+            .filter(|p| inside(rect, p.min_x, p.min_y, p.max_x, p.max_y))
+            .map(|p| Platform { min_x: p.min_x - min_x, min_y: p.min_y - min_y, max_x: p.max_x - min_x, max_y: p.max_y - min_y, ..p.clone() })
+            .collect();
+
+        let mut stairs = Vec::new();
+        for (idx, s) in self.stairs.iter().enumerate() {
+            if !inside(rect, s.min_x, s.min_y, s.max_x, s.max_y) { continue; }
+            let mut cells = Vec::new();
+            for y in s.min_y..=s.max_y { for x in s.min_x..=s.max_x {
+                if self.stairs_map[y][x] == Some(idx) { cells.push((x - min_x, y - min_y)); }
+            } }
+            if cells.is_empty() { continue; }
+            stairs.push((Stairs { min_x: s.min_x - min_x, min_y: s.min_y - min_y, max_x: s.max_x - min_x, max_y: s.max_y - min_y, ..s.clone() }, cells));
+        }
+
+        Some(Clip { width: max_x - min_x + 1, height: max_y - min_y + 1, tiles, platforms, stairs })
+    }
+
+    // Whether the clip can be placed with its top-left corner at (x, y): it must fit in the level
+    // and none of its structures may straddle a module border
+    pub fn can_place_clip(&self, clip: &Clip, x: usize, y: usize) -> bool {
+        if x + clip.width > self.width || y + clip.height > self.height { return false; }
+        let same_module = |a: usize, b: usize| self.module_index_for_x(x + a) == self.module_index_for_x(x + b);
+        clip.platforms.iter().all(|p| same_module(p.min_x, p.max_x)) && clip.stairs.iter().all(|(s, _)| same_module(s.min_x, s.max_x))
+    }
+
+    // Reset every cell in the rectangle to air; stairs it touches are dropped whole, see compact_stairs.
+    // Platforms are re-segmented when the surrounding operation finishes
+    fn erase_rect(&mut self, rect: CellRect) {
+        let (min_x, min_y, max_x, max_y) = rect;
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                self.tiles[y][x] = Tile::default();
+                self.stairs_map[y][x] = None;
+            }
+        }
+        self.compact_stairs();
+    }
+
+    pub(super) fn place_clip(&mut self, clip: &Clip, x: usize, y: usize) {
+        self.erase_rect((x, y, x + clip.width - 1, y + clip.height - 1));
+        for (dy, row) in clip.tiles.iter().enumerate() {
+            for (dx, tile) in row.iter().enumerate() { self.tiles[y + dy][x + dx] = tile.clone(); }
+        }
+        for (s, cells) in &clip.stairs {
+            let idx = self.stairs.len();
+            self.stairs.push(Stairs { min_x: s.min_x + x, min_y: s.min_y + y, max_x: s.max_x + x, max_y: s.max_y + y, ..s.clone() });
+            for &(cx, cy) in cells { self.stairs_map[cy + y][cx + x] = Some(idx); }
+        }
+
+        // Locked platforms go in as they were; the rebuild keeps them since their tiles came along
+        for p in clip.platforms.iter().filter(|p| p.locked) {
+            self.platforms.push(Platform { min_x: p.min_x + x, min_y: p.min_y + y, max_x: p.max_x + x, max_y: p.max_y + y, ..p.clone() });
+        }
+
+        // Re-segment now so copied platforms that come out with the same bounds keep their metadata
+        self.rebuild_platforms();
+        for p in &clip.platforms {
+            let (min_x, min_y, max_x, max_y) = (p.min_x + x, p.min_y + y, p.max_x + x, p.max_y + y);
+            if let Some(target) = self.platforms.iter_mut().find(|t| (t.min_x, t.min_y, t.max_x, t.max_y) == (min_x, min_y, max_x, max_y)) {
+                target.metadata = p.metadata.clone();
+            }
+        }
+    }
+
+    // Clear a rectangle as one undoable step
+    pub fn cut_rect(&mut self, rect: CellRect) {
+        let Some(rect) = self.clamp_rect(rect) else { return };
+        self.finish_operation();
+        self.begin_operation("Cut");
+        self.erase_rect(rect);
+        self.finish_operation();
+    }
+
+    // Paste as one undoable step; returns false (and changes nothing) if the clip can't go there
+    pub fn paste_clip(&mut self, clip: &Clip, x: usize, y: usize) -> bool {
+        if !self.can_place_clip(clip, x, y) { return false; }
+        self.finish_operation();
+        self.begin_operation("Paste");
+        self.place_clip(clip, x, y);
+        self.clear_duplicate_ids_in((x, y, x + clip.width - 1, y + clip.height - 1));
+        // Platforms are already re-segmented with the copied metadata, no need to rebuild again
+        self.auto_assign_ids();
+        self.commit_operation();
+        true
+    }
+
+    // Move a rectangle's content so its top-left corner lands on (x, y), as one undoable step
+    pub fn move_rect(&mut self, rect: CellRect, x: usize, y: usize) -> bool {
+        let Some(rect) = self.clamp_rect(rect) else { return false };
+        let Some(clip) = self.copy_rect(rect) else { return false };
+        if (x, y) == (rect.0, rect.1) || !self.can_place_clip(&clip, x, y) { return false; }
+        self.finish_operation();
+        self.begin_operation("Move Selection");
+        self.erase_rect(rect);
+        self.place_clip(&clip, x, y);
+        self.auto_assign_ids();
+        self.commit_operation();
+        true
+    }
+}
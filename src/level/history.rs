@@ -1,108 +1,290 @@
-use crate::tile::TileType;
-
-// Represents a single tile change operation
-#[derive(Clone, Debug)]
-pub struct TileChange {
-    pub x: usize,
-    pub y: usize,
-    pub old_tile: TileType,
-    pub new_tile: TileType,
-}
-
-// Represents a group of tile changes that should be undone/redone together
-#[derive(Clone, Debug)]
-pub struct TileOperation {
-    pub changes: Vec<TileChange>,
-    pub description: String,
-}
-
-impl TileOperation {
-    pub fn new(description: String) -> Self {
-        Self {
-            changes: Vec::new(),
-            description,
-        }
-    }
-
-    pub fn add_change(&mut self, x: usize, y: usize, old_tile: TileType, new_tile: TileType) {
-        self.changes.push(TileChange {
-            x,
-            y,
-            old_tile,
-            new_tile,
-        });
-    }
-
-    pub fn is_empty(&self) -> bool {
-        self.changes.is_empty()
-    }
-}
-
-// Main history manager for undo/redo functionality
-pub struct HistoryManager {
-    undo_stack: Vec<TileOperation>,
-    redo_stack: Vec<TileOperation>,
-    max_history_size: usize,
-}
-
-impl HistoryManager {
-    pub fn new(max_history_size: usize) -> Self {
-        Self {
-            undo_stack: Vec::new(),
-            redo_stack: Vec::new(),
-            max_history_size,
-        }
-    }
-
-    pub fn add_operation(&mut self, operation: TileOperation) {
-        // Clear redo stack when new operation is added
-        self.redo_stack.clear();
-        
-        // Only add non-empty operations
-        if !operation.is_empty() {
-            self.undo_stack.push(operation);
-            
-            // Limit history size
-            if self.undo_stack.len() > self.max_history_size {
-                self.undo_stack.remove(0);
-            }
-        }
-    }
-
-    pub fn can_undo(&self) -> bool {
-        !self.undo_stack.is_empty()
-    }
-
-    pub fn can_redo(&self) -> bool {
-        !self.redo_stack.is_empty()
-    }
-
-    pub fn undo(&mut self) -> Option<TileOperation> {
-        self.undo_stack.pop().map(|operation| {
-            // Move to redo stack
-            self.redo_stack.push(operation.clone());
-            operation
-        })
-    }
-
-    pub fn redo(&mut self) -> Option<TileOperation> {
-        self.redo_stack.pop().map(|operation| {
-            // Move back to undo stack
-            self.undo_stack.push(operation.clone());
-            operation
-        })
-    }
-
-    pub fn clear(&mut self) {
-        self.undo_stack.clear();
-        self.redo_stack.clear();
-    }
-
-    pub fn undo_count(&self) -> usize {
-        self.undo_stack.len()
-    }
-
-    pub fn redo_count(&self) -> usize {
-        self.redo_stack.len()
-    }
-} 
\ No newline at end of file
+use crate::tile::{Tile, Platform, Stairs};
+use crate::tile_type_system::MetaField;
+use crate::level::{Segmentation, ModuleProperties, ObjectRef};
+
+// Everything a structural edit can change. Undoing one swaps the whole state, so tiles, metadata,
+// structures, modules and the segmentation always come back consistent with each other
+#[derive(Clone, Debug, PartialEq)]
+pub struct LevelState {
+    pub tiles: Vec<Vec<Tile>>,
+    pub width: usize,
+    pub height: usize,
+    pub platforms: Vec<Platform>,
+    pub platform_map: Vec<Vec<Option<usize>>>,
+    pub stairs: Vec<Stairs>,
+    pub stairs_map: Vec<Vec<Option<usize>>>,
+    pub modules: Vec<usize>,
+    pub module_properties: Vec<ModuleProperties>,
+    pub theme: String,
+    pub segmentation: Segmentation,
+}
+
+// One side of a change. Edits that only touch some objects' metadata, one module's properties
+// or the theme keep just those values, so typing into a field never copies the grid
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    Snapshot(LevelState),
+    Metadata(Vec<(ObjectRef, Vec<MetaField>)>),
+    ModuleProperties(usize, ModuleProperties),
+    Theme(String),
+}
+
+impl Change {
+    // Cells copied into the change; the history's memory budget is counted in these
+    pub fn cells(&self) -> usize {
+        match self {
+            Change::Snapshot(state) => state.width * state.height,
+            Change::Metadata(objects) => objects.len(),
+            Change::ModuleProperties(..) | Change::Theme(_) => 1,
+        }
+    }
+}
+
+// An operation that is still being recorded, e.g. a brush stroke while the mouse is down
+#[derive(Clone, Debug)]
+pub struct PendingOperation {
+    pub description: String,
+    pub before: Change,
+    pub merge_key: Option<String>,
+}
+
+impl PendingOperation {
+    pub fn is_snapshot(&self) -> bool { matches!(self.before, Change::Snapshot(_)) }
+}
+
+// Represents a group of changes that should be undone/redone together. Entries keep a single
+// side: on the undo stack the values before the change, on the redo stack the values after it.
+// The other side is always the live level, so it is captured when the entry is undone or redone
+#[derive(Clone, Debug)]
+pub struct HistoryEntry {
+    pub description: String,
+    pub change: Change,
+    // Consecutive entries with the same key collapse into one (e.g. typing into a metadata field)
+    pub merge_key: Option<String>,
+    // Identifies the state after this entry; set by the history manager
+    pub revision: u64,
+}
+
+// Main history manager for undo/redo functionality
+pub struct HistoryManager {
+    undo_stack: Vec<HistoryEntry>,
+    redo_stack: Vec<HistoryEntry>,
+    max_history_size: usize,
+    // Snapshots of a large level are big, so old entries also go once the stacks hold more cells
+    // than this. The newest entry always stays, however large
+    max_history_cells: usize,
+    // Whether the next entry may still merge into the top one. Closed once the edit that opened
+    // it is over, so editing a field again later becomes its own undo step
+    merge_open: bool,
+    // Revisions name states so a saved one can be recognised after undo/redo. `base_revision`
+    // is the state below the oldest entry, which changes when old entries are dropped
+    next_revision: u64,
+    base_revision: u64,
+}
+
+impl HistoryManager {
+    pub fn new(max_history_size: usize, max_history_cells: usize) -> Self {
+        Self {
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            max_history_size,
+            max_history_cells,
+            merge_open: false,
+            next_revision: 1,
+            base_revision: 0,
+        }
+    }
+
+    // `before` is what the operation started from; callers skip operations that changed nothing
+    pub fn add_operation(&mut self, description: String, before: Change, merge_key: Option<String>) {
+        let revision = self.next_revision;
+        self.next_revision += 1;
+
+        // Clear redo stack when new operation is added
+        self.redo_stack.clear();
+
+        let merge = self.merge_open && merge_key.is_some();
+        self.merge_open = merge_key.is_some();
+        if let Some(top) = self.undo_stack.last_mut().filter(|top| merge && top.merge_key == merge_key) {
+            top.revision = revision;
+            return;
+        }
+
+        self.undo_stack.push(HistoryEntry { description, change: before, merge_key, revision });
+
+        // Limit history size
+        let mut cells: usize = self.undo_stack.iter().map(|e| e.change.cells()).sum();
+        while self.undo_stack.len() > self.max_history_size || (self.undo_stack.len() > 1 && cells > self.max_history_cells) {
+            let dropped = self.undo_stack.remove(0);
+            cells -= dropped.change.cells();
+            self.base_revision = dropped.revision;
+        }
+    }
+
+    pub fn close_merge(&mut self) {
+        self.merge_open = false;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    // The entry to undo; the caller applies it and hands back the other side with `push_redo`
+    pub fn pop_undo(&mut self) -> Option<HistoryEntry> {
+        self.merge_open = false;
+        self.undo_stack.pop()
+    }
+
+    pub fn push_redo(&mut self, entry: HistoryEntry) {
+        self.redo_stack.push(entry);
+    }
+
+    // The entry to redo; the caller applies it and hands back the other side with `push_undo`
+    pub fn pop_redo(&mut self) -> Option<HistoryEntry> {
+        self.merge_open = false;
+        self.redo_stack.pop()
+    }
+
+    pub fn push_undo(&mut self, entry: HistoryEntry) {
+        self.undo_stack.push(entry);
+    }
+
+    pub fn undo_description(&self) -> Option<&str> {
+        self.undo_stack.last().map(|e| e.description.as_str())
+    }
+
+    pub fn redo_description(&self) -> Option<&str> {
+        self.redo_stack.last().map(|e| e.description.as_str())
+    }
+
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.merge_open = false;
+        self.base_revision = self.next_revision;
+        self.next_revision += 1;
+    }
+
+    // The revision of the current state
+    pub fn revision(&self) -> u64 {
+        self.undo_stack.last().map_or(self.base_revision, |e| e.revision)
+    }
+
+    pub fn undo_count(&self) -> usize {
+        self.undo_stack.len()
+    }
+
+    pub fn redo_count(&self) -> usize {
+        self.redo_stack.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::level::test_level;
+    use crate::tile::TileType;
+
+    #[test]
+    fn edits_merge_only_while_the_window_is_open() {
+        let mut level = test_level(4, 4);
+        level.set_theme("a");
+        level.set_theme("ab");
+        level.end_merging();
+        level.set_theme("abc");
+        assert_eq!(level.history.undo_count(), 2);
+        level.undo();
+        assert_eq!(level.theme(), "ab");
+        level.undo();
+        assert_eq!(level.theme(), "default");
+    }
+
+    #[test]
+    fn other_operations_close_the_merge_window() {
+        let mut level = test_level(4, 4);
+        level.set_theme("a");
+        level.set_tile(0, 0, TileType::Custom("ground".into()));
+        level.finish_operation();
+        level.set_theme("b");
+        level.set_theme("c");
+        assert_eq!(level.history.undo_count(), 3);
+    }
+
+    #[test]
+    fn undo_and_redo_restore_each_state() {
+        let mut level = test_level(4, 4);
+        let ground = TileType::Custom("ground".into());
+        let mut states = vec![level.capture_state()];
+        for x in 0..3 {
+            level.set_tile(x, 3, ground.clone());
+            level.finish_operation();
+            states.push(level.capture_state());
+        }
+        for expected in states.iter().rev().skip(1) {
+            level.undo();
+            assert_eq!(&level.capture_state(), expected);
+        }
+        assert!(!level.can_undo());
+        assert_eq!(level.history.redo_count(), 3);
+        for expected in states.iter().skip(1) {
+            level.redo();
+            assert_eq!(&level.capture_state(), expected);
+        }
+        assert!(!level.can_redo());
+    }
+
+    #[test]
+    fn operations_without_changes_are_not_recorded() {
+        let mut level = test_level(4, 4);
+        level.begin_operation("Nothing");
+        level.finish_operation();
+        level.set_theme("default");
+        assert!(!level.can_undo());
+    }
+
+    #[test]
+    fn field_edits_record_only_what_they_change() {
+        let mut level = test_level(4, 4);
+        level.set_tile(0, 3, TileType::Custom("pig".into()));
+        level.finish_operation();
+        let object = crate::level::ObjectRef::Tile(0, 3);
+        let mut fields = level.metadata_of(object).unwrap().clone();
+        crate::level::set_id(&mut fields, "boss".to_string());
+        level.set_metadata(object, fields.clone());
+        level.set_module_properties(0, ModuleProperties { name: "start".into(), ..Default::default() });
+        level.set_theme("cave");
+        let kinds: Vec<bool> = level.history.undo_stack.iter().map(|e| matches!(e.change, Change::Snapshot(_))).collect();
+        assert_eq!(kinds, vec![true, false, false, false]);
+
+        let edited = level.capture_state();
+        level.undo();
+        level.undo();
+        level.undo();
+        assert_eq!((level.theme(), level.module_properties(0).name.as_str(), level.object_id(object)), ("default", "", Some("")));
+        level.redo();
+        level.redo();
+        level.redo();
+        assert_eq!(level.capture_state(), edited);
+    }
+
+    #[test]
+    fn snapshots_are_capped_by_cell_count() {
+        let mut level = test_level(10, 10);
+        level.history = HistoryManager::new(100, 250);
+        for x in 0..5 {
+            level.set_tile(x, 9, TileType::Custom("ground".into()));
+            level.finish_operation();
+        }
+        assert_eq!(level.history.undo_count(), 2);
+        // Small entries barely count against the budget
+        for theme in ["a", "b", "c"] {
+            level.set_theme(theme);
+            level.end_merging();
+        }
+        assert_eq!(level.history.undo_count(), 5);
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::{HashMap, HashSet};
+use crate::tile::TileType;
+use crate::tile_manifest::StructureKind;
+use crate::tile_type_system::MetaField;
+use crate::level::{Level, ObjectRef, CellRect};
+
+// objectIDs are free text, but the editor can hand out unique ones of the form <kind>_<n>.
+// The highest number handed out per kind is kept in `id_counters` (saved with the project, untouched
+// by undo), so an id that was deleted is not given to a different object.
+
+impl Level {
+    pub fn object_id(&self, object: ObjectRef) -> Option<&str> {
+        self.metadata_of(object).and_then(|fields| id_of(fields))
+    }
+
+    // Whether some exported object other than `except` already uses the id
+    pub fn id_in_use(&self, id: &str, except: Option<ObjectRef>) -> bool {
+        !id.is_empty() && self.exported_objects().into_iter().any(|(o, _)| Some(o) != except && self.object_id(o) == Some(id))
+    }
+
+    fn id_prefix(&self, object: ObjectRef) -> String {
+        let structure_key = |kind: StructureKind, fallback: &str| {
+            self.registry.kinds().iter().find(|k| k.structure == Some(kind)).map_or_else(|| fallback.to_string(), |k| k.key.clone())
+        };
+        match object {
+            ObjectRef::Platform(_) => structure_key(StructureKind::Platform, "platform"),
+            ObjectRef::Stairs(_) => structure_key(StructureKind::Stairs, "stairs"),
+            ObjectRef::Tile(x, y) => match &self.tiles[y][x].tile_type {
+                TileType::Custom(k) => k.clone(),
+                TileType::Air => "air".to_string(),
+            },
+        }
+    }
+
+    // Next unused id for the object's kind, e.g. pig_3 when pig_2 is the highest so far; the number is used up
+    pub fn next_object_id(&mut self, object: ObjectRef) -> String {
+        let in_use = self.highest_ids_in_use();
+        self.issue_id(object, &in_use)
+    }
+
+    fn issue_id(&mut self, object: ObjectRef, in_use: &HashMap<String, u32>) -> String {
+        let prefix = self.id_prefix(object);
+        let counter = self.id_counters.get(&prefix).copied().unwrap_or(0);
+        let number = counter.max(in_use.get(&prefix).copied().unwrap_or(0)) + 1;
+        self.id_counters.insert(prefix.clone(), number);
+        format!("{}_{}", prefix, number)
+    }
+
+    // Highest <n> per prefix among the ids of the form <prefix>_<n> currently in the level
+    fn highest_ids_in_use(&self) -> HashMap<String, u32> {
+        let mut highest = HashMap::new();
+        for (object, _) in self.exported_objects() {
+            let Some((prefix, number)) = self.object_id(object).and_then(|id| id.rsplit_once('_')) else { continue };
+            let Ok(number) = number.parse::<u32>() else { continue };
+            let entry = highest.entry(prefix.to_string()).or_insert(0);
+            *entry = number.max(*entry);
+        }
+        highest
+    }
+
+    // Give every exported object without an id a fresh one as one undoable step; returns how many got one
+    pub fn assign_missing_ids(&mut self) -> usize {
+        self.finish_operation();
+        self.begin_operation("Assign Object IDs");
+        let assigned = self.fill_missing_ids();
+        self.commit_operation();
+        assigned
+    }
+
+    // Give each of the objects a fresh id of its kind as one undoable step
+    pub fn generate_ids(&mut self, objects: &[ObjectRef]) {
+        self.finish_operation();
+        self.begin_operation("Generate Object IDs");
+        let in_use = self.highest_ids_in_use();
+        for &object in objects {
+            let id = self.issue_id(object, &in_use);
+            if let Some(fields) = self.metadata_of_mut(object) { set_id(fields, id); }
+        }
+        self.commit_operation();
+    }
+
+    fn fill_missing_ids(&mut self) -> usize {
+        let mut assigned = 0;
+        let in_use = self.highest_ids_in_use();
+        for (object, _) in self.exported_objects() {
+            if self.object_id(object).is_some_and(|id| id.is_empty()) {
+                let id = self.issue_id(object, &in_use);
+                if let Some(fields) = self.metadata_of_mut(object) { set_id(fields, id); }
+                assigned += 1;
+            }
+        }
+        assigned
+    }
+
+    // With auto ids on, objects created by the operation being committed get their id right away.
+    // Metadata-only operations create nothing and only record the objects they edit
+    pub(crate) fn auto_assign_ids(&mut self) {
+        if self.auto_ids && self.current_operation.as_ref().is_some_and(|op| op.is_snapshot()) {
+            self.fill_missing_ids();
+        }
+    }
+
+    // Pasted copies must not share ids with the objects they were copied from
+    pub(crate) fn clear_duplicate_ids_in(&mut self, rect: CellRect) {
+        let (min_x, min_y, max_x, max_y) = rect;
+        let inside = |cell: (usize, usize)| (min_x..=max_x).contains(&cell.0) && (min_y..=max_y).contains(&cell.1);
+        let objects = self.exported_objects();
+        let outside_ids: HashSet<String> = objects.iter()
+            .filter(|(_, cell)| !inside(*cell))
+            .filter_map(|&(o, _)| self.object_id(o).map(str::to_string))
+            .collect();
+        for (object, cell) in objects {
+            if inside(cell) && self.object_id(object).is_some_and(|id| outside_ids.contains(id))
+                && let Some(fields) = self.metadata_of_mut(object) {
+                set_id(fields, String::new());
+            }
+        }
+    }
+
+}
+
+pub(super) fn id_of(fields: &[MetaField]) -> Option<&str> {
+    fields.iter().find_map(|f| match f {
+        MetaField::Text { key, value, .. } if key == "objectID" => Some(value.as_str()),
+        _ => None,
+    })
+}
+
+pub(crate) fn set_id(fields: &mut [MetaField], id: String) {
+    for f in fields.iter_mut() {
+        if let MetaField::Text { key, value, .. } = f && key == "objectID" { *value = id; return; }
+    }
+}
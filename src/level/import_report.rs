@@ -0,0 +1,88 @@
+use std::fmt;
+
+// What happened to a game object that could not be imported exactly as written
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IssueKind {
+    Skipped,     // Object was not placed at all
+    Clamped,     // Object was placed, but cut to the level / module bounds
+    Defaulted,   // A missing or malformed field was replaced by a default value
+    UnknownKind, // The type string matched no registered tile kind
+}
+
+impl IssueKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            IssueKind::Skipped => "Skipped",
+            IssueKind::Clamped => "Clamped",
+            IssueKind::Defaulted => "Defaulted",
+            IssueKind::UnknownKind => "Unknown kind",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ImportIssue {
+    pub module_index: usize,
+    pub object_index: Option<usize>, // None for issues with the module itself
+    pub kind: IssueKind,
+    pub reason: String,
+}
+
+impl fmt::Display for ImportIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.object_index {
+            Some(oi) => write!(f, "module {}, object {}: {} ({})", self.module_index, oi, self.kind.name(), self.reason),
+            None => write!(f, "module {}: {} ({})", self.module_index, self.kind.name(), self.reason),
+        }
+    }
+}
+
+// Per-object diagnostics collected while importing a level
+#[derive(Clone, Debug, Default)]
+pub struct ImportReport {
+    pub issues: Vec<ImportIssue>,
+}
+
+impl ImportReport {
+    pub fn push(&mut self, module_index: usize, object_index: Option<usize>, kind: IssueKind, reason: impl Into<String>) {
+        self.issues.push(ImportIssue { module_index, object_index, kind, reason: reason.into() });
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+#[derive(Debug)]
+pub enum ImportError {
+    Io(std::io::Error),
+    Parse(serde_json::Error),
+    Migration(String),
+    // Strict mode: the document parsed, but at least one object could not be imported exactly
+    Rejected(ImportReport),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Io(e) => write!(f, "cannot read level file: {}", e),
+            ImportError::Parse(e) => write!(f, "invalid level json: {}", e),
+            ImportError::Migration(e) => write!(f, "cannot upgrade level json: {}", e),
+            ImportError::Rejected(report) => write!(f, "strict import rejected the file ({} issues)", report.issues.len()),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+impl From<std::io::Error> for ImportError {
+    fn from(e: std::io::Error) -> Self {
+        ImportError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for ImportError {
+    fn from(e: serde_json::Error) -> Self {
+        ImportError::Parse(e)
+    }
+}
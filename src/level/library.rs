@@ -0,0 +1,58 @@
+use serde::{Serialize, Deserialize};
+use serde::de::Error as _;
+use crate::level::{Level, Clip, ModuleProperties};
+
+// A module saved for reuse in other levels: its columns with every tile, structure and
+// metadata field, plus a name and tags to find it by. Stored one per file in the library folder.
+pub const LIBRARY_EXTENSION: &str = "psmod";
+const LIBRARY_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LibraryModule {
+    version: u32,
+    pub name: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub properties: ModuleProperties, // Taken over by the module the entry is inserted as
+    pub clip: Clip,
+}
+
+impl LibraryModule {
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn from_json(json_str: &str) -> serde_json::Result<LibraryModule> {
+        let module: LibraryModule = serde_json::from_str(json_str)?;
+        if module.version > LIBRARY_VERSION {
+            return Err(serde_json::Error::custom(format!("library module version {} is newer than supported version {}", module.version, LIBRARY_VERSION)));
+        }
+        let clip = &module.clip;
+        if clip.width == 0 || clip.tiles.len() != clip.height || clip.tiles.iter().any(|row| row.len() != clip.width) {
+            return Err(serde_json::Error::custom("library module grid does not match its width/height"));
+        }
+        let fits = |min_x: usize, min_y: usize, max_x: usize, max_y: usize| min_x <= max_x && min_y <= max_y && max_x < clip.width && max_y < clip.height;
+        if !clip.platforms.iter().all(|p| fits(p.min_x, p.min_y, p.max_x, p.max_y))
+            || !clip.stairs.iter().all(|(s, cells)| fits(s.min_x, s.min_y, s.max_x, s.max_y) && cells.iter().all(|&(x, y)| fits(x, y, x, y))) {
+            return Err(serde_json::Error::custom("library module has a structure outside its grid"));
+        }
+        Ok(module)
+    }
+
+    // Case-insensitive match against the name and tags; an empty filter matches everything
+    pub fn matches(&self, filter: &str) -> bool {
+        let filter = filter.trim().to_lowercase();
+        filter.is_empty() || self.name.to_lowercase().contains(&filter) || self.tags.iter().any(|t| t.to_lowercase().contains(&filter))
+    }
+}
+
+impl Level {
+    pub fn module_to_library(&self, index: usize, name: &str, tags: Vec<String>) -> Option<LibraryModule> {
+        let span = *self.modules.get(index)?;
+        if self.height == 0 { return None; }
+        let start = self.module_start(index);
+        let clip = self.copy_rect((start, 0, start + span - 1, self.height - 1))?;
+        Some(LibraryModule { version: LIBRARY_VERSION, name: name.trim().to_string(), tags, properties: self.module_properties(index), clip })
+    }
+}
@@ -0,0 +1,199 @@
+use serde_json::Value;
+
+// Version written by export_to_json. Bump this and append a step to MIGRATIONS whenever
+// the LevelData / ModuleData / gameObjects shape changes.
+pub const FORMAT_VERSION: u64 = 4;
+
+type Migration = fn(&mut Value) -> Result<(), String>;
+
+// MIGRATIONS[n] upgrades a document from version n to version n + 1
+const MIGRATIONS: &[Migration] = &[
+    migrate_v0_to_v1,
+    migrate_v1_to_v2,
+    migrate_v2_to_v3,
+    migrate_v3_to_v4,
+];
+
+// Upgrade an exported level document step by step until it matches FORMAT_VERSION.
+// Documents without a formatVersion field predate versioning and count as version 0.
+pub fn migrate_to_current(doc: &mut Value) -> Result<(), String> {
+    let mut version = match doc.get("formatVersion") {
+        None => 0,
+        Some(v) => v.as_u64().ok_or_else(|| format!("formatVersion must be a non-negative integer, got {}", v))?,
+    };
+    if version > FORMAT_VERSION {
+        return Err(format!("formatVersion {} is newer than supported version {}", version, FORMAT_VERSION));
+    }
+    while version < FORMAT_VERSION {
+        MIGRATIONS[version as usize](doc)?;
+        version += 1;
+        set_version(doc, version)?;
+    }
+    Ok(())
+}
+
+fn set_version(doc: &mut Value, version: u64) -> Result<(), String> {
+    let obj = doc.as_object_mut().ok_or("level document must be a JSON object")?;
+    obj.insert("formatVersion".to_string(), Value::from(version));
+    Ok(())
+}
+
+fn game_objects_mut(doc: &mut Value) -> impl Iterator<Item = &mut Value> {
+    doc.get_mut("modules")
+        .and_then(|m| m.as_array_mut())
+        .into_iter()
+        .flatten()
+        .filter_map(|m| m.get_mut("gameObjects").and_then(|g| g.as_array_mut()))
+        .flatten()
+}
+
+// v0 wrote stairs orientation as a string ("-1" / "1", or "left" / "right"); v1 writes an integer
+fn migrate_v0_to_v1(doc: &mut Value) -> Result<(), String> {
+    for obj in game_objects_mut(doc) {
+        let is_stairs = obj.get("type").and_then(|t| t.as_str()).is_some_and(|t| t.eq_ignore_ascii_case("stairs"));
+        if !is_stairs { continue; }
+        if let Some(orientation) = obj.get_mut("orientation")
+            && let Some(s) = orientation.as_str() {
+            let parsed: i64 = match s.trim().to_ascii_lowercase().as_str() {
+                "left" => -1,
+                "right" => 1,
+                other => other.parse().map_err(|_| format!("invalid stairs orientation {:?}", s))?,
+            };
+            *orientation = Value::from(parsed);
+        }
+    }
+    Ok(())
+}
+
+// v1 wrote stairs one row below their bottom-left cell (platforms and tiles were already
+// bottom-left) and always built them from wall; v2 aligns stairs and records their tile type
+fn migrate_v1_to_v2(doc: &mut Value) -> Result<(), String> {
+    for obj in game_objects_mut(doc) {
+        let is_stairs = obj.get("type").and_then(|t| t.as_str()).is_some_and(|t| t.eq_ignore_ascii_case("stairs"));
+        if !is_stairs { continue; }
+        if let Some(y) = obj.get_mut("position").and_then(|p| p.get_mut("y"))
+            && let Some(v) = y.as_u64() {
+            *y = Value::from(v + 1);
+        }
+        if let Some(obj) = obj.as_object_mut() {
+            obj.entry("tileType").or_insert_with(|| Value::from("wall"));
+        }
+    }
+    Ok(())
+}
+
+// v3 only adds optional module properties (name, difficulty, tags, textureTheme, musicCue);
+// v2 documents are valid v3 documents without them
+fn migrate_v2_to_v3(_doc: &mut Value) -> Result<(), String> {
+    Ok(())
+}
+
+// v4 records the level height (levelHeight). Older documents were written at whatever height the
+// editor had, so they are imported at the current height, as before
+fn migrate_v3_to_v4(_doc: &mut Value) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn stairs(orientation: Value) -> Value {
+        json!({ "type": "stairs", "position": { "x": 1, "y": 0 }, "size": 2, "orientation": orientation, "objectID": "" })
+    }
+
+    fn level(version: Option<u64>, objects: Vec<Value>) -> Value {
+        let mut doc = json!({ "levelID": "test", "textureTheme": "default", "modules": [{ "moduleID": 0, "xSpan": 10, "gameObjects": objects }] });
+        if let Some(v) = version { doc["formatVersion"] = Value::from(v); }
+        doc
+    }
+
+    fn first_object(doc: &Value) -> &Value {
+        &doc["modules"][0]["gameObjects"][0]
+    }
+
+    #[test]
+    fn v0_string_orientations_become_integers() {
+        for (written, expected) in [("left", -1), ("right", 1), ("-1", -1), ("1", 1), (" Left ", -1)] {
+            let mut doc = level(Some(0), vec![stairs(Value::from(written))]);
+            migrate_v0_to_v1(&mut doc).unwrap();
+            assert_eq!(first_object(&doc)["orientation"], Value::from(expected), "{:?}", written);
+        }
+    }
+
+    #[test]
+    fn v0_integer_orientation_is_kept() {
+        let mut doc = level(Some(0), vec![stairs(Value::from(-1))]);
+        migrate_v0_to_v1(&mut doc).unwrap();
+        assert_eq!(first_object(&doc)["orientation"], Value::from(-1));
+    }
+
+    #[test]
+    fn v0_unknown_orientation_is_an_error() {
+        let mut doc = level(Some(0), vec![stairs(Value::from("up"))]);
+        assert!(migrate_to_current(&mut doc).is_err());
+    }
+
+    #[test]
+    fn missing_format_version_counts_as_v0() {
+        let mut doc = level(None, vec![stairs(Value::from("right"))]);
+        migrate_to_current(&mut doc).unwrap();
+        assert_eq!(doc["formatVersion"], Value::from(FORMAT_VERSION));
+        assert_eq!(first_object(&doc)["orientation"], Value::from(1));
+    }
+
+    #[test]
+    fn newer_version_is_rejected() {
+        let mut doc = level(Some(FORMAT_VERSION + 1), vec![]);
+        let before = doc.clone();
+        assert!(migrate_to_current(&mut doc).is_err());
+        assert_eq!(doc, before);
+    }
+
+    #[test]
+    fn non_integer_version_is_rejected() {
+        let mut doc = level(None, vec![]);
+        doc["formatVersion"] = Value::from("2");
+        assert!(migrate_to_current(&mut doc).is_err());
+    }
+
+    #[test]
+    fn v1_stairs_move_up_a_row_and_default_to_wall() {
+        let ground = json!({ "type": "Ground", "position": { "x": 0, "y": 0 }, "size": { "x": 3, "y": 1 } });
+        let mut doc = level(Some(1), vec![stairs(Value::from(1)), ground.clone()]);
+        doc["modules"][0]["gameObjects"][0]["position"]["y"] = Value::from(2);
+        migrate_to_current(&mut doc).unwrap();
+        let objects = &doc["modules"][0]["gameObjects"];
+        assert_eq!(objects[0]["position"]["y"], Value::from(3));
+        assert_eq!(objects[0]["tileType"], Value::from("wall"));
+        assert_eq!(objects[1], ground);
+    }
+
+    #[test]
+    fn v1_stairs_keep_an_existing_tile_type() {
+        let mut doc = level(Some(1), vec![stairs(Value::from(1))]);
+        doc["modules"][0]["gameObjects"][0]["tileType"] = Value::from("ground");
+        migrate_to_current(&mut doc).unwrap();
+        assert_eq!(first_object(&doc)["tileType"], Value::from("ground"));
+    }
+
+    #[test]
+    fn v0_chains_to_current() {
+        let mut doc = level(None, vec![stairs(Value::from("left"))]);
+        migrate_to_current(&mut doc).unwrap();
+        let stairs = first_object(&doc);
+        assert_eq!(doc["formatVersion"], Value::from(FORMAT_VERSION));
+        assert_eq!(stairs["orientation"], Value::from(-1));
+        assert_eq!(stairs["position"]["y"], Value::from(1));
+        assert_eq!(stairs["tileType"], Value::from("wall"));
+    }
+
+    #[test]
+    fn current_version_is_left_alone() {
+        let mut doc = level(Some(FORMAT_VERSION), vec![stairs(Value::from(1))]);
+        let before = doc.clone();
+        migrate_to_current(&mut doc).unwrap();
+        assert_eq!(doc, before);
+    }
+}
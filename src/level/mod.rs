@@ -1,16 +1,60 @@
 pub mod history;
 use history::*;
 use crate::tile::*;
+use crate::tile_manifest::StructureKind;
+use std::rc::Rc;
+use std::collections::BTreeMap;
 
 use egui_macroquad::macroquad::prelude::*;
 use serde::Serialize;
 use crate::tile_type_system::MetaField;
 use serde_json::json;
 mod platform_ext;
+mod project;
+mod migration;
+mod import_report;
+mod clipboard;
+mod presets;
+mod ids;
+mod segmentation;
+mod modules;
+mod library;
+pub mod validate;
+pub use import_report::{ImportReport, ImportError, IssueKind};
+pub use validate::{Diagnostic, Severity};
+pub use project::PROJECT_EXTENSION;
+pub use clipboard::{Clip, CellRect};
+pub use presets::MetadataPreset;
+pub use segmentation::Segmentation;
+pub use library::{LibraryModule, LIBRARY_EXTENSION};
+pub use modules::ModuleProperties;
+pub(crate) use ids::set_id;
 
 // Constants
 const GRID_LINE_WIDTH: f32 = 0.05;
 const TILE_SIZE: f32 = 1.0;
+pub const MAX_LEVEL_HEIGHT: usize = 1000;
+// Imported documents are held to these so a bad xSpan can't allocate an enormous grid
+pub const MAX_MODULE_SPAN: usize = 1000;
+pub const MAX_LEVEL_WIDTH: usize = 10_000;
+pub const DEFAULT_THEME: &str = "default";
+// Snapshot cells a level's history may hold, about 100 steps of a 500x40 level
+const MAX_HISTORY_CELLS: usize = 2_000_000;
+
+// The edge rows are added at or removed from when the level height changes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HeightEdge {
+    Top,    // Game positions (counted from the bottom row) stay the same
+    Bottom, // The editor's rows stay the same; everything moves up or down in the game
+}
+
+// A metadata-carrying object in the level, as shown by the inspector
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ObjectRef {
+    Tile(usize, usize),
+    Platform(usize),
+    Stairs(usize),
+}
 
 // Level structure
 pub struct Level {
@@ -18,7 +62,7 @@ pub struct Level {
     pub(crate) width: usize,
     pub(crate) height: usize,
     history: HistoryManager,
-    pub(crate) current_operation: Option<TileOperation>,
+    pub(crate) current_operation: Option<PendingOperation>,
     pub(crate) highlighted_tiles: Vec<(usize, usize)>, // (x, y) coordinates of tiles to highlight
     pub(crate) platforms: Vec<Platform>,
     pub(crate) platform_map: Vec<Vec<Option<usize>>>, // index into platforms
@@ -26,16 +70,30 @@ pub struct Level {
     pub(crate) stairs_map: Vec<Vec<Option<usize>>>, // index into stairs
     // Modules: sequence of x-spans. Borders are cumulative sums starting at 0
     pub(crate) modules: Vec<usize>,
+    pub(crate) module_properties: Vec<ModuleProperties>, // Same order as modules
+    pub(crate) theme: String, // Exported as the level's textureTheme
+    // Shared tile kinds: platform grouping, default metadata and export names
+    pub(crate) registry: Rc<TileRegistry>,
+    // Per-kind metadata presets, saved with the project
+    pub(crate) presets: Vec<MetadataPreset>,
+    // Hand out objectIDs to new objects when an operation finishes
+    pub(crate) auto_ids: bool,
+    // Highest objectID number handed out per kind; only ever grows, saved with the project
+    pub(crate) id_counters: BTreeMap<String, u32>,
+    // How unlocked platform tiles are split into rectangles
+    pub(crate) segmentation: Segmentation,
+    // History revision the level was last saved at; None after a change history doesn't track
+    saved_revision: Option<u64>,
 }
 
 impl Level {
-    pub fn new(width: usize, height: usize) -> Self {
+    pub fn new(width: usize, height: usize, registry: Rc<TileRegistry>) -> Self {
         let tiles = vec![vec![Tile::default(); width]; height];
         Self {
             tiles,
             width,
             height,
-            history: HistoryManager::new(100), // Allow up to 100 operations
+            history: HistoryManager::new(100, MAX_HISTORY_CELLS), // Allow up to 100 operations
             current_operation: None,
             highlighted_tiles: Vec::new(),
             platforms: Vec::new(),
@@ -43,33 +101,56 @@ impl Level {
             stairs: Vec::new(),
             stairs_map: vec![vec![None; width]; height],
             modules: Vec::new(),
+            module_properties: Vec::new(),
+            theme: DEFAULT_THEME.to_string(),
+            registry,
+            presets: Vec::new(),
+            auto_ids: false,
+            id_counters: BTreeMap::new(),
+            segmentation: Segmentation::default(),
+            saved_revision: Some(0),
         }
     }
 
+    // A blank level made of the given module spans
+    pub fn with_modules(modules: &[usize], height: usize, theme: &str, registry: Rc<TileRegistry>) -> Self {
+        let mut level = Level::new(0, height.clamp(1, MAX_LEVEL_HEIGHT), registry);
+        level.modules = modules.iter().map(|&span| span.max(1)).collect();
+        level.theme = theme.trim().to_string();
+        if level.theme.is_empty() { level.theme = DEFAULT_THEME.to_string(); }
+        level.apply_modules_as_width();
+        level
+    }
+
+    pub fn registry(&self) -> &TileRegistry { &self.registry }
+
     pub fn set_tile(&mut self, x: usize, y: usize, tile_type: TileType) {
         if x < self.width && y < self.height {
             let old_tile_type = self.tiles[y][x].tile_type.clone();
             if old_tile_type != tile_type {
                 // Start a new operation if we don't have one
-                if self.current_operation.is_none() {
-                    self.current_operation = Some(TileOperation::new("Tile Placement".to_string()));
-                }
-                
-                // Add the change to the current operation
-                if let Some(ref mut operation) = self.current_operation {
-                    operation.add_change(x, y, old_tile_type, tile_type.clone());
-                }
-                
-                self.tiles[y][x].set_tile_type(tile_type);
+                self.begin_operation("Tile Placement");
+
+                self.tiles[y][x].set_tile_type(tile_type, &self.registry);
 
                 // Opportunistically update platforms locally for maintainability
                 self.try_update_platforms_locally(x, y);
-                // When changing a tile type, clear its stairs membership. Creators will reassign.
-                self.stairs_map[y][x] = None;
+                // Overwriting a stairs cell breaks the stairs; compacting drops them
+                if self.stairs_map[y][x].take().is_some() { self.compact_stairs(); }
             }
         }
     }
 
+    // Place a tile and, if a stamp is given, give it those metadata values instead of the defaults
+    pub fn set_tile_stamped(&mut self, x: usize, y: usize, tile_type: TileType, stamp: Option<&[MetaField]>) {
+        self.set_tile(x, y, tile_type.clone());
+        if let Some(fields) = stamp
+            && x < self.width && y < self.height && self.tiles[y][x].tile_type == tile_type && self.tiles[y][x].metadata != fields {
+            self.begin_operation("Tile Placement");
+            self.tiles[y][x].metadata = fields.to_vec();
+        }
+    }
+
     pub fn get_tile(&self, x: usize, y: usize) -> Option<&Tile> {
         if x < self.width && y < self.height {
             Some(&self.tiles[y][x])
@@ -88,25 +169,52 @@ impl Level {
 
     pub fn fill_rectangle(&mut self, min_x: usize, min_y: usize, max_x: usize, max_y: usize, tile_type: TileType) {
         // Start a new operation for the fill
-        self.current_operation = Some(TileOperation::new("Fill Area".to_string()));
+        self.begin_operation("Fill Area");
         
         for y in min_y..=max_y {
             for x in min_x..=max_x {
                 if x < self.width && y < self.height {
-                    let old_tile_type = self.tiles[y][x].tile_type.clone();
-                    if old_tile_type != tile_type {
-                        // Add the change to the current operation
-                        if let Some(ref mut operation) = self.current_operation {
-                            operation.add_change(x, y, old_tile_type, tile_type.clone());
-                        }
-                        
-                        self.tiles[y][x].set_tile_type(tile_type.clone());
+                    if self.tiles[y][x].tile_type != tile_type {
+                        self.tiles[y][x].set_tile_type(tile_type.clone(), &self.registry);
                     }
                 }
             }
         }
     }
 
+    // Flood-fill the 4-connected region of same-typed tiles around (x, y), optionally without leaving
+    // its module. Stairs cells bound the region. Tiles are written directly so platforms are
+    // re-segmented once when the operation finishes instead of per cell
+    pub fn flood_fill(&mut self, x: usize, y: usize, tile_type: TileType, within_module: bool, stamp: Option<&[MetaField]>) {
+        if x >= self.width || y >= self.height { return; }
+        let target = self.tiles[y][x].tile_type.clone();
+        if target == tile_type || self.stairs_map[y][x].is_some() { return; }
+
+        let (lo, hi) = if within_module {
+            let start = self.module_borders().into_iter().filter(|&b| b <= x).max().unwrap_or(0);
+            (start, self.module_end_for_x(x))
+        } else {
+            (0, self.width)
+        };
+
+        self.begin_operation("Flood Fill");
+        let mut visited = vec![vec![false; self.width]; self.height];
+        let mut stack = vec![(x, y)];
+        visited[y][x] = true;
+        while let Some((cx, cy)) = stack.pop() {
+            self.tiles[cy][cx].set_tile_type(tile_type.clone(), &self.registry);
+            if let Some(fields) = stamp { self.tiles[cy][cx].metadata = fields.to_vec(); }
+            let neighbors = [(cx.wrapping_sub(1), cy), (cx + 1, cy), (cx, cy.wrapping_sub(1)), (cx, cy + 1)];
+            for (nx, ny) in neighbors {
+                if nx < lo || nx >= hi || ny >= self.height || visited[ny][nx] { continue; }
+                if self.tiles[ny][nx].tile_type != target || self.stairs_map[ny][nx].is_some() { continue; }
+                visited[ny][nx] = true;
+                stack.push((nx, ny));
+            }
+        }
+        self.finish_operation();
+    }
+
     pub fn set_highlighted_tiles(&mut self, tiles: Vec<(usize, usize)>) {
         self.highlighted_tiles = tiles;
     }
@@ -125,32 +233,150 @@ impl Level {
         self.highlighted_tiles.clear();
     }
 
-    pub fn finish_operation(&mut self) {
-        if let Some(operation) = self.current_operation.take() {
-            if !operation.is_empty() {
-                self.history.add_operation(operation);
-                self.rebuild_platforms();
-            }
+    // Start recording an undoable operation. Does nothing if one is already being recorded,
+    // so a brush stroke that touches many cells still becomes a single step
+    pub fn begin_operation(&mut self, description: &str) {
+        if self.current_operation.as_ref().is_some_and(|op| !op.is_snapshot()) { self.commit_operation(); }
+        if self.current_operation.is_none() {
+            self.current_operation = Some(PendingOperation { description: description.to_string(), before: Change::Snapshot(self.capture_state()), merge_key: None });
         }
     }
 
-    pub fn undo(&mut self) {
-        if let Some(operation) = self.history.undo() {
-            // Apply the reverse of the operation
-            for change in &operation.changes {
-                self.tiles[change.y][change.x].set_tile_type(change.old_tile.clone());
-            }
+    // Like begin_operation, but consecutive operations with the same key merge into one history entry
+    pub fn begin_merging_operation(&mut self, description: &str, merge_key: String) {
+        self.begin_operation(description);
+        if let Some(op) = self.current_operation.as_mut() { op.merge_key = Some(merge_key); }
+    }
+
+    // Start a merging operation that only records `before`, the values it is about to change.
+    // Inside a pending snapshot the edit is simply part of that operation
+    fn begin_targeted_operation(&mut self, description: &str, merge_key: String, before: Change) {
+        if self.current_operation.as_ref().is_some_and(|op| !op.is_snapshot()) { self.commit_operation(); }
+        match self.current_operation.as_mut() {
+            Some(op) => op.merge_key = Some(merge_key),
+            None => self.current_operation = Some(PendingOperation { description: description.to_string(), before, merge_key: Some(merge_key) }),
+        }
+    }
+
+    pub fn finish_operation(&mut self) {
+        // Tile edits re-segment platforms; metadata-only edits leave them as they are
+        let tiles_changed = match self.current_operation.as_ref().map(|op| &op.before) {
+            Some(Change::Snapshot(before)) => before.width != self.width || before.height != self.height || before.tiles.iter().flatten().zip(self.tiles.iter().flatten()).any(|(a, b)| a.tile_type != b.tile_type),
+            _ => false,
+        };
+        if tiles_changed {
             self.rebuild_platforms();
         }
+        self.auto_assign_ids();
+        self.commit_operation();
+    }
+
+    // Record the pending operation exactly as the level looks now
+    pub(crate) fn commit_operation(&mut self) {
+        if let Some(op) = self.current_operation.take()
+            && op.before != self.capture(&op.before) {
+            self.history.add_operation(op.description, op.before, op.merge_key);
+        }
+    }
+
+    pub fn undo(&mut self) {
+        self.finish_operation();
+        let Some(HistoryEntry { description, change, merge_key, revision }) = self.history.pop_undo() else { return };
+        let current = self.capture(&change);
+        self.apply(change);
+        self.history.push_redo(HistoryEntry { description, change: current, merge_key, revision });
     }
 
     pub fn redo(&mut self) {
-        if let Some(operation) = self.history.redo() {
-            // Apply the operation
-            for change in &operation.changes {
-                self.tiles[change.y][change.x].set_tile_type(change.new_tile.clone());
+        self.finish_operation();
+        let Some(HistoryEntry { description, change, merge_key, revision }) = self.history.pop_redo() else { return };
+        let current = self.capture(&change);
+        self.apply(change);
+        self.history.push_undo(HistoryEntry { description, change: current, merge_key, revision });
+    }
+
+    pub fn undo_description(&self) -> Option<&str> {
+        self.history.undo_description()
+    }
+
+    // End the current run of merging edits, e.g. when the field being typed into loses focus
+    pub fn end_merging(&mut self) {
+        self.history.close_merge();
+    }
+
+    // Unsaved changes: the level moved away from the state it was saved at, by any route
+    pub fn is_dirty(&self) -> bool {
+        self.saved_revision != Some(self.history.revision())
+    }
+
+    pub fn mark_saved(&mut self) {
+        self.finish_operation();
+        self.saved_revision = Some(self.history.revision());
+    }
+
+    // For changes that are saved with the project but not undoable (presets, auto ids)
+    pub(crate) fn mark_modified(&mut self) {
+        self.saved_revision = None;
+    }
+
+    pub fn redo_description(&self) -> Option<&str> {
+        self.history.redo_description()
+    }
+
+    fn capture_state(&self) -> LevelState {
+        LevelState {
+            tiles: self.tiles.clone(),
+            width: self.width,
+            height: self.height,
+            platforms: self.platforms.clone(),
+            platform_map: self.platform_map.clone(),
+            stairs: self.stairs.clone(),
+            stairs_map: self.stairs_map.clone(),
+            modules: self.modules.clone(),
+            module_properties: self.module_properties.clone(),
+            theme: self.theme.clone(),
+            segmentation: self.segmentation,
+        }
+    }
+
+    fn restore_state(&mut self, state: LevelState) {
+        self.tiles = state.tiles;
+        self.width = state.width;
+        self.height = state.height;
+        self.platforms = state.platforms;
+        self.platform_map = state.platform_map;
+        self.stairs = state.stairs;
+        self.stairs_map = state.stairs_map;
+        self.modules = state.modules;
+        self.module_properties = state.module_properties;
+        self.theme = state.theme;
+        self.segmentation = state.segmentation;
+        self.highlighted_tiles.clear();
+    }
+
+    // The level's current values for whatever `like` records
+    fn capture(&self, like: &Change) -> Change {
+        match like {
+            Change::Snapshot(_) => Change::Snapshot(self.capture_state()),
+            Change::Metadata(objects) => Change::Metadata(objects.iter().map(|(o, _)| (*o, self.metadata_of(*o).cloned().unwrap_or_default())).collect()),
+            Change::ModuleProperties(index, _) => Change::ModuleProperties(*index, self.module_properties(*index)),
+            Change::Theme(_) => Change::Theme(self.theme.clone()),
+        }
+    }
+
+    fn apply(&mut self, change: Change) {
+        match change {
+            Change::Snapshot(state) => self.restore_state(state),
+            Change::Metadata(objects) => {
+                for (object, fields) in objects {
+                    if let Some(metadata) = self.metadata_of_mut(object) { *metadata = fields; }
+                }
             }
-            self.rebuild_platforms();
+            Change::ModuleProperties(index, properties) => {
+                self.module_properties.resize(self.modules.len(), ModuleProperties::default());
+                if let Some(slot) = self.module_properties.get_mut(index) { *slot = properties; }
+            }
+            Change::Theme(theme) => self.theme = theme,
         }
     }
 
@@ -170,7 +396,7 @@ impl Level {
         self.height
     }
 
-    // Resize the level width (x-size). Y-size remains static
+    // Resize the level width (x-size), adding or removing columns at the right edge
     pub fn resize_width(&mut self, new_width: usize) {
         if new_width == self.width { return; }
         if new_width > self.width {
@@ -194,18 +420,85 @@ impl Level {
             }
             self.width = new_width;
             // Cleanup stairs vector to remove any entries no longer referenced
-            self.compact_stairs_after_resize();
+            self.compact_stairs();
         }
         // Rebuild platforms to reflect new width
         self.rebuild_platforms();
     }
 
-    fn compact_stairs_after_resize(&mut self) {
+    // Resize the level height, adding blank rows at or removing rows from `edge`. Structures reaching
+    // into removed rows are dropped, leaving their remaining cells as plain tiles. Part of the caller's operation
+    pub fn resize_height(&mut self, new_height: usize, edge: HeightEdge) {
+        let new_height = new_height.clamp(1, MAX_LEVEL_HEIGHT);
+        if new_height == self.height { return; }
+        let shift = match edge { HeightEdge::Top => new_height as isize - self.height as isize, HeightEdge::Bottom => 0 };
+        let new_y = |y: usize| usize::try_from(y as isize + shift).ok().filter(|&y| y < new_height);
+
+        let mut tiles = vec![vec![Tile::default(); self.width]; new_height];
+        let mut stairs_map = vec![vec![None; self.width]; new_height];
+        for y in 0..self.height {
+            if let Some(ny) = new_y(y) {
+                tiles[ny] = std::mem::take(&mut self.tiles[y]);
+                stairs_map[ny] = std::mem::take(&mut self.stairs_map[y]);
+            }
+        }
+        for (i, s) in self.stairs.iter_mut().enumerate() {
+            match (new_y(s.min_y), new_y(s.max_y)) {
+                (Some(min_y), Some(max_y)) => { s.min_y = min_y; s.max_y = max_y; }
+                _ => for cell in stairs_map.iter_mut().flatten() { if *cell == Some(i) { *cell = None; } },
+            }
+        }
+        // Shifted platforms are the rebuild's reference, so the ones that still fit keep their metadata
+        self.platforms.retain_mut(|p| match (new_y(p.min_y), new_y(p.max_y)) {
+            (Some(min_y), Some(max_y)) => { p.min_y = min_y; p.max_y = max_y; true }
+            _ => false,
+        });
+
+        self.height = new_height;
+        self.tiles = tiles;
+        self.stairs_map = stairs_map;
+        self.platform_map = vec![vec![None; self.width]; new_height];
+        self.compact_stairs();
+        self.rebuild_platforms();
+    }
+
+    // Dragging the height changes it every frame; merge that into one undo step
+    pub fn set_height(&mut self, new_height: usize, edge: HeightEdge) {
+        if new_height.clamp(1, MAX_LEVEL_HEIGHT) == self.height { return; }
+        self.finish_operation();
+        self.begin_merging_operation("Level Height", format!("level_height:{:?}", edge));
+        self.resize_height(new_height, edge);
+        self.auto_assign_ids();
+        self.commit_operation();
+    }
+
+    pub fn theme(&self) -> &str { &self.theme }
+
+    // Typing a theme changes it every frame; merge that into one undo step
+    pub fn set_theme(&mut self, theme: &str) {
+        if self.theme == theme { return; }
+        self.finish_operation();
+        self.begin_targeted_operation("Level Theme", "level_theme".to_string(), Change::Theme(self.theme.clone()));
+        self.theme = theme.to_string();
+        self.commit_operation();
+    }
+
+    // Drop stairs no longer referenced by any cell and renumber the rest. Stairs are one object,
+    // so ones that lost a cell inside the level are dropped too, leaving their other cells as plain tiles
+    pub(crate) fn compact_stairs(&mut self) {
         use std::collections::BTreeSet;
+        let mut mapped = vec![0usize; self.stairs.len()];
+        for idx in self.stairs_map.iter().flatten().flatten() { mapped[*idx] += 1; }
+        let broken: Vec<bool> = self.stairs.iter().enumerate().map(|(i, s)| {
+            let cells: Vec<(usize, usize)> = s.cells().into_iter().filter(|&(x, y)| x < self.width && y < self.height).collect();
+            mapped[i] != cells.len() || cells.iter().any(|&(x, y)| self.stairs_map[y][x] != Some(i))
+        }).collect();
         let mut used: BTreeSet<usize> = BTreeSet::new();
-        for y in 0..self.height {
-            for x in 0..self.width {
-                if let Some(idx) = self.stairs_map[y][x] { used.insert(idx); }
+        for cell in self.stairs_map.iter_mut().flatten() {
+            match *cell {
+                Some(idx) if broken[idx] => *cell = None,
+                Some(idx) => { used.insert(idx); }
+                None => {}
             }
         }
         if used.is_empty() {
@@ -323,7 +616,7 @@ impl Level {
         }
     }
 
-    pub fn draw(&self, registry: &TileRegistry) {
+    pub fn draw(&self) {
         // Draw the base level
         for y in 0..self.height {
             for x in 0..self.width {
@@ -334,7 +627,7 @@ impl Level {
                     TILE_SIZE,
                     TILE_SIZE,
                 );
-                if let Some(tex) = registry.texture_for(&tile.tile_type) {
+                if let Some(tex) = self.registry.texture_for(&tile.tile_type) {
                     draw_texture_ex(tex, rect.x, rect.y, WHITE, DrawTextureParams { dest_size: Some(vec2(rect.w, rect.h)), ..Default::default() });
                 } else {
                     // Fallback color for Air or missing textures
@@ -345,6 +638,18 @@ impl Level {
             }
         }
 
+        // Hand-made platforms keep a faint outline so they can be told apart from automatic ones
+        for p in self.platforms.iter().filter(|p| p.locked) {
+            draw_rectangle_lines(
+                p.min_x as f32 * TILE_SIZE,
+                p.min_y as f32 * TILE_SIZE,
+                p.width() as f32 * TILE_SIZE,
+                p.height() as f32 * TILE_SIZE,
+                0.08,
+                Color::new(0.1, 0.3, 0.9, 0.8),
+            );
+        }
+
         // Draw highlighted tiles
         for &(x, y) in &self.highlighted_tiles {
             let rect = Rect::new(
@@ -396,6 +701,19 @@ impl Level {
         }
     }
 
+    // Outline of a multi-cell selection
+    pub fn draw_selection_rect(&self, rect: (usize, usize, usize, usize)) {
+        let (min_x, min_y, max_x, max_y) = rect;
+        draw_rectangle_lines(
+            min_x as f32 * TILE_SIZE,
+            min_y as f32 * TILE_SIZE,
+            (max_x - min_x + 1) as f32 * TILE_SIZE,
+            (max_y - min_y + 1) as f32 * TILE_SIZE,
+            0.1,
+            RED,
+        );
+    }
+
     pub fn stairs_at(&self, x: usize, y: usize) -> Option<&Stairs> {
         if x < self.width && y < self.height {
             if let Some(idx) = self.stairs_map[y][x] { self.stairs.get(idx) } else { None }
@@ -408,18 +726,144 @@ impl Level {
         } else { None }
     }
 
-    pub fn assign_stairs_with_cells(&mut self, t: TileType, cells: &[(usize, usize)], orientation: i32) -> usize {
-        if cells.is_empty() { return self.stairs.len(); }
-        let (mut min_x, mut min_y, mut max_x, mut max_y) = (usize::MAX, usize::MAX, 0usize, 0usize);
-        for &(x, y) in cells {
-            min_x = min_x.min(x); min_y = min_y.min(y); max_x = max_x.max(x); max_y = max_y.max(y);
+    // The object the inspector shows for a cell: stairs win over platforms, platforms over the tile itself
+    pub fn object_at(&self, x: usize, y: usize) -> Option<ObjectRef> {
+        if x >= self.width || y >= self.height { return None; }
+        if let Some(idx) = self.stairs_map[y][x] { return Some(ObjectRef::Stairs(idx)); }
+        if let Some(idx) = self.platform_map[y][x] { return Some(ObjectRef::Platform(idx)); }
+        Some(ObjectRef::Tile(x, y))
+    }
+
+    pub fn metadata_of(&self, object: ObjectRef) -> Option<&Vec<MetaField>> {
+        match object {
+            ObjectRef::Tile(x, y) => self.get_tile(x, y).map(|t| &t.metadata),
+            ObjectRef::Platform(idx) => self.platforms.get(idx).map(|p| &p.metadata),
+            ObjectRef::Stairs(idx) => self.stairs.get(idx).map(|s| &s.metadata),
+        }
+    }
+
+    pub fn tile_type_of(&self, object: ObjectRef) -> Option<&TileType> {
+        match object {
+            ObjectRef::Tile(x, y) => self.get_tile(x, y).map(|t| &t.tile_type),
+            ObjectRef::Platform(idx) => self.platforms.get(idx).map(|p| &p.tile_type),
+            ObjectRef::Stairs(idx) => self.stairs.get(idx).map(|s| &s.tile_type),
+        }
+    }
+
+    fn metadata_of_mut(&mut self, object: ObjectRef) -> Option<&mut Vec<MetaField>> {
+        match object {
+            ObjectRef::Tile(x, y) => self.get_tile_mut(x, y).map(|t| &mut t.metadata),
+            ObjectRef::Platform(idx) => self.platforms.get_mut(idx).map(|p| &mut p.metadata),
+            ObjectRef::Stairs(idx) => self.stairs.get_mut(idx).map(|s| &mut s.metadata),
         }
+    }
+
+    // Replace an object's metadata as one undoable step. Consecutive edits of the same object
+    // merge, so typing an ID character by character doesn't flood the history
+    pub fn set_metadata(&mut self, object: ObjectRef, fields: Vec<MetaField>) {
+        let Some(before) = self.metadata_of(object).cloned() else { return };
+        self.begin_targeted_operation("Edit Metadata", format!("metadata:{:?}", object), Change::Metadata(vec![(object, before)]));
+        if let Some(metadata) = self.metadata_of_mut(object) { *metadata = fields; }
+        self.commit_operation();
+    }
+
+    // Every metadata-carrying object touching the rect, each once; air tiles have none
+    pub fn objects_in_rect(&self, rect: CellRect) -> Vec<ObjectRef> {
+        let (min_x, min_y, max_x, max_y) = rect;
+        let mut objects = Vec::new();
+        for y in min_y..=max_y.min(self.height.saturating_sub(1)) {
+            for x in min_x..=max_x.min(self.width.saturating_sub(1)) {
+                let Some(object) = self.object_at(x, y) else { continue };
+                if object == ObjectRef::Tile(x, y) && self.tiles[y][x].tile_type == TileType::Air { continue; }
+                if !objects.contains(&object) { objects.push(object); }
+            }
+        }
+        objects
+    }
+
+    // Editable fields present on all the objects, each paired with whether their values differ.
+    // objectID is left out: one value written to every object would only make duplicates
+    pub fn common_metadata(&self, objects: &[ObjectRef]) -> Vec<(MetaField, bool)> {
+        let Some(first) = objects.first().and_then(|&o| self.metadata_of(o)) else { return Vec::new() };
+        let mut common = Vec::new();
+        for field in first.iter().filter(|f| f.is_editable() && f.key() != "objectID") {
+            let mut mixed = false;
+            let everywhere = objects[1..].iter().all(|&o| {
+                match self.metadata_of(o).and_then(|fields| fields.iter().find(|f| f.same_field(field))) {
+                    Some(other) => { mixed |= other != field; true }
+                    None => false,
+                }
+            });
+            if everywhere { common.push((field.clone(), mixed)); }
+        }
+        common
+    }
+
+    // Give one field the same value on all objects as a single undoable step
+    pub fn set_metadata_field(&mut self, objects: &[ObjectRef], field: &MetaField) {
+        let before = objects.iter().filter_map(|&o| Some((o, self.metadata_of(o)?.clone()))).collect();
+        self.begin_targeted_operation("Edit Metadata", format!("metadata:{}:{:?}", field.key(), objects), Change::Metadata(before));
+        for &object in objects {
+            if let Some(target) = self.metadata_of_mut(object).and_then(|fields| fields.iter_mut().find(|f| f.same_field(field))) {
+                target.set_value_from(field);
+            }
+        }
+        self.commit_operation();
+    }
+
+    // Add stairs as part of the current (or a new "Place Stairs") operation, overwriting the cells
+    // they cover; returns the new index
+    pub fn place_stairs(&mut self, t: TileType, x: usize, y: usize, size: usize, orientation: StairsOrientation) -> usize {
+        self.begin_operation("Place Stairs");
+        let metadata = self.registry.structure_metadata(StructureKind::Stairs);
+        let index = self.add_stairs(Stairs::new(t, x, y, size, orientation, metadata));
+        // Cells that already held the stairs' tile type don't trigger a re-segmentation on their own
+        self.rebuild_platforms();
+        index
+    }
+
+    // Cells outside the level are dropped and stairs it overlaps are broken up; platforms are
+    // left for the caller to rebuild
+    fn add_stairs(&mut self, stairs: Stairs) -> usize {
         let new_index = self.stairs.len();
-        let mut metadata = default_stairs_metadata_for(t.clone());
-        metadata.push(MetaField::Label { label: "Orientation".to_string(), value: orientation.to_string() });
-        self.stairs.push(Stairs { tile_type: t.clone(), min_x, min_y, max_x, max_y, metadata });
-        for &(x, y) in cells { if x < self.width && y < self.height { self.stairs_map[y][x] = Some(new_index); } }
-        new_index
+        let mut overlapped = false;
+        for (x, y) in stairs.cells() {
+            if x < self.width && y < self.height {
+                self.tiles[y][x].set_tile_type(stairs.tile_type.clone(), &self.registry);
+                overlapped |= self.stairs_map[y][x].replace(new_index).is_some();
+            }
+        }
+        self.stairs.push(stairs);
+        if overlapped { self.compact_stairs(); }
+        self.stairs.len() - 1
+    }
+
+    // Resize, flip or retype stairs as one undoable step. The bottom-left corner stays put; returns
+    // false (and changes nothing) if the new shape leaves the level or its module or hits other stairs
+    pub fn set_stairs_shape(&mut self, index: usize, size: usize, orientation: StairsOrientation, t: TileType) -> bool {
+        let Some(old) = self.stairs.get(index) else { return false };
+        if size == 0 || size > old.max_y + 1 || old.min_x + size > self.width { return false; }
+        let stairs = Stairs::new(t, old.min_x, old.max_y + 1 - size, size, orientation, old.metadata.clone());
+        if self.module_index_for_x(stairs.min_x) != self.module_index_for_x(stairs.max_x) { return false; }
+        let cells = stairs.cells();
+        if cells.iter().any(|&(x, y)| self.stairs_map[y][x].is_some_and(|i| i != index)) { return false; }
+
+        self.finish_operation();
+        self.begin_operation("Edit Stairs");
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.stairs_map[y][x] == Some(index) { self.tiles[y][x] = Tile::default(); self.stairs_map[y][x] = None; }
+            }
+        }
+        for &(x, y) in &cells {
+            self.tiles[y][x].set_tile_type(stairs.tile_type.clone(), &self.registry);
+            self.stairs_map[y][x] = Some(index);
+        }
+        self.stairs[index] = stairs;
+        self.rebuild_platforms();
+        self.auto_assign_ids();
+        self.commit_operation();
+        true
     }
 
     pub fn modules(&self) -> &Vec<usize> { &self.modules }
@@ -437,6 +881,7 @@ impl Level {
 
     // Recompute width = sum of module spans and resize level accordingly
     pub fn apply_modules_as_width(&mut self) {
+        self.module_properties.resize(self.modules.len(), ModuleProperties::default());
         let new_width = self.modules.iter().copied().sum::<usize>().max(0);
         self.resize_width(new_width);
         self.enforce_module_boundaries_for_structures();
@@ -493,16 +938,21 @@ impl Level {
 
 #[derive(Serialize, serde::Deserialize)]
 struct LevelData {
+    #[serde(rename = "formatVersion", default)]
+    format_version: u64,
     #[serde(rename = "levelID")]
     name: String,
+    // Rows the bottom-based y positions count in; documents without it use the editor's current height
+    #[serde(rename = "levelHeight", default, skip_serializing_if = "Option::is_none")]
+    height: Option<usize>,
     modules: Vec<ModuleData>,
     #[serde(rename = "textureTheme")]
     texture_theme: String,
 }
 
 impl LevelData {
-    pub fn new(name: String, modules: Vec<ModuleData>) -> Self {
-        Self { name, modules, texture_theme: "default".to_string() }
+    pub fn new(name: String, height: usize, modules: Vec<ModuleData>, texture_theme: String) -> Self {
+        Self { format_version: migration::FORMAT_VERSION, name, height: Some(height), modules, texture_theme }
     }
 }
 
@@ -512,6 +962,8 @@ struct ModuleData {
     module_id: usize,
     #[serde(rename = "xSpan")]
     x_span: usize,
+    #[serde(flatten)]
+    properties: ModuleProperties,
     #[serde(rename = "gameObjects")]
     game_objects: Vec<serde_json::Value>,
 }
@@ -523,7 +975,7 @@ struct Position { x: usize, y: usize }
 struct Size { x: usize, y: usize }
 
 impl Level {
-    pub fn export_to_json(&self, name: String, registry: &TileRegistry) -> serde_json::Result<String> {
+    pub fn export_to_json(&self, name: String) -> serde_json::Result<String> {
         let _borders = self.module_borders();
         let mut modules: Vec<ModuleData> = Vec::new();
         let mut start_x = 0usize;
@@ -535,13 +987,13 @@ impl Level {
             for p in &self.platforms {
                 if p.min_x >= start_x && p.max_x < end_x {
                     // Type should be the platform's tile type display name (e.g., "Wall", "Ground")
-                    let type_name = display_name_for_tile_type(registry, &p.tile_type).unwrap_or_else(|| "Platform".to_string());
+                    let type_name = export_name_for_tile_type(&self.registry, &p.tile_type).unwrap_or_else(|| "Platform".to_string());
                     let object_id = get_meta_text(&p.metadata, "objectID").unwrap_or_default();
                     game_objects.push(json!({
                         "type": type_name,
                         "position": { "x": p.min_x - start_x, "y": self.height - 1 - p.max_y },
                         "size": { "x": p.max_x - p.min_x + 1, "y": p.max_y - p.min_y + 1 },
-                        "enabled": true,
+                        "enabled": get_meta_bool(&p.metadata, "enabled", true),
                         "mutable": get_meta_bool(&p.metadata, "mutable", false),
                         "objectID": object_id,
                     }));
@@ -551,15 +1003,14 @@ impl Level {
             // Stairs fully contained within module
             for s in &self.stairs {
                 if s.min_x >= start_x && s.max_x < end_x {
-                    let size = (s.max_x - s.min_x + 1).max(s.max_y - s.min_y + 1);
                     let object_id = get_meta_text(&s.metadata, "objectID").unwrap_or_default();
-                    let orientation = get_meta_label(&s.metadata, "Orientation").unwrap_or_else(|| "0".to_string());
                     game_objects.push(json!({
                         "type": "stairs",
-                        "position": { "x": s.min_x - start_x, "y": self.height - s.max_y - 2},
-                        "size": size,
-                        "orientation": orientation,
-                        "enabled": true,
+                        "position": { "x": s.min_x - start_x, "y": self.height - 1 - s.max_y },
+                        "size": s.size,
+                        "tileType": export_name_for_tile_type(&self.registry, &s.tile_type).unwrap_or_else(|| "wall".to_string()),
+                        "orientation": s.orientation.to_game(),
+                        "enabled": get_meta_bool(&s.metadata, "enabled", true),
                         "mutable": get_meta_bool(&s.metadata, "mutable", false),
                         "objectID": object_id,
                     }));
@@ -572,7 +1023,7 @@ impl Level {
                     if self.platform_at(x, y).is_none() && self.stairs_at(x, y).is_none() {
                         let t = &self.tiles[y][x];
                         if let TileType::Custom(k) = &t.tile_type {
-                            let kind = display_name_for_tile_type(registry, &t.tile_type).unwrap_or_else(|| k.clone());
+                            let kind = export_name_for_tile_type(&self.registry, &t.tile_type).unwrap_or_else(|| k.clone());
                             let object_id = get_meta_text(&t.metadata, "objectID");
                             game_objects.push(json!({
                                 "type": kind,
@@ -586,11 +1037,11 @@ impl Level {
                 }
             }
 
-            modules.push(ModuleData { module_id: i, x_span: span, game_objects });
+            modules.push(ModuleData { module_id: i, x_span: span, properties: self.module_properties(i), game_objects });
             start_x = end_x;
         }
 
-        let export = LevelData { name, modules, texture_theme: "default".to_string() };
+        let export = LevelData::new(name, self.height, modules, self.theme.clone());
         serde_json::to_string_pretty(&export)
     }
 }
@@ -616,28 +1067,70 @@ fn get_meta_number(fields: &[MetaField], key: &str) -> Option<f32> {
     None
 }
 
-fn display_name_for_tile_type(registry: &TileRegistry, t: &TileType) -> Option<String> {
+fn export_name_for_tile_type(registry: &TileRegistry, t: &TileType) -> Option<String> {
     match t {
         TileType::Air => Some("Air".to_string()),
-        TileType::Custom(k) => registry.get(k).map(|tk| tk.display_name.clone()),
+        TileType::Custom(k) => registry.get(k).map(|tk| tk.export_name.clone()),
     }
 }
 
-fn get_meta_label(fields: &[MetaField], label: &str) -> Option<String> {
-    for f in fields {
-        if let MetaField::Label { label: l, value } = f { if *l == label { return Some(value.clone()); } }
+impl Level {
+    // Import a game level. Objects that can't be placed exactly as written are listed in the
+    // returned report; in strict mode any such object rejects the whole file and leaves the level untouched.
+    pub fn import_from_json(&mut self, json_str: &str, strict: bool) -> Result<ImportReport, ImportError> {
+        // Parse, then bring older documents up to the current format before reading them
+        let mut doc: serde_json::Value = serde_json::from_str(json_str)?;
+        migration::migrate_to_current(&mut doc).map_err(ImportError::Migration)?;
+        let parsed: LevelData = serde_json::from_value(doc)?;
+
+        let height = match parsed.height {
+            None => self.height,
+            Some(h) if (1..=MAX_LEVEL_HEIGHT).contains(&h) => h,
+            Some(h) => return Err(ImportError::Parse(serde::de::Error::custom(format!("levelHeight {} is not between 1 and {}", h, MAX_LEVEL_HEIGHT)))),
+        };
+        let width: usize = parsed.modules.iter().map(|m| m.x_span.clamp(1, MAX_MODULE_SPAN)).sum();
+        if width > MAX_LEVEL_WIDTH {
+            return Err(ImportError::Parse(serde::de::Error::custom(format!("modules add up to {} columns, more than the maximum of {}", width, MAX_LEVEL_WIDTH))));
+        }
+
+        // Build into a staging level so a rejected import doesn't clobber the current one
+        let mut staged = Level::new(0, height, self.registry.clone());
+        staged.segmentation = self.segmentation;
+        staged.theme = parsed.texture_theme.clone();
+        let report = staged.place_imported_objects(&parsed);
+        if strict && !report.is_clean() {
+            return Err(ImportError::Rejected(report));
+        }
+
+        self.finish_operation();
+        self.begin_operation("Import JSON");
+        self.width = staged.width;
+        self.height = staged.height;
+        self.modules = staged.modules;
+        self.module_properties = staged.module_properties;
+        self.theme = staged.theme;
+        self.tiles = staged.tiles;
+        self.platforms = staged.platforms;
+        self.platform_map = staged.platform_map;
+        self.stairs = staged.stairs;
+        self.stairs_map = staged.stairs_map;
+        self.highlighted_tiles.clear();
+        self.commit_operation();
+        Ok(report)
     }
-    None
-}
 
-impl Level {
-    pub fn import_from_json(&mut self, json_str: &str, registry: &TileRegistry) -> serde_json::Result<()> {
-        // Parse
-        let parsed: LevelData = serde_json::from_str(json_str)?;
+    fn place_imported_objects(&mut self, parsed: &LevelData) -> ImportReport {
+        let registry = self.registry.clone();
+        let mut report = ImportReport::default();
 
         // Apply modules and resize width
         self.modules.clear();
-        for m in &parsed.modules { self.modules.push(m.x_span.max(1)); }
+        for (mi, m) in parsed.modules.iter().enumerate() {
+            if m.x_span == 0 { report.push(mi, None, IssueKind::Defaulted, "xSpan 0 widened to 1"); }
+            if m.x_span > MAX_MODULE_SPAN { report.push(mi, None, IssueKind::Clamped, format!("xSpan {} cut to {}", m.x_span, MAX_MODULE_SPAN)); }
+            self.modules.push(m.x_span.clamp(1, MAX_MODULE_SPAN));
+        }
+        self.module_properties = parsed.modules.iter().map(|m| m.properties.clone()).collect();
         self.apply_modules_as_width();
 
         // Clear tiles and structures
@@ -645,61 +1138,105 @@ impl Level {
         self.platforms.clear();
         self.stairs.clear();
 
-        // Place objects per module
+        // Place objects per module. Platforms get their metadata once they are segmented, at the
+        // rect they were placed at: (module, object, rect, tile type)
+        let mut placed_platforms: Vec<(usize, usize, CellRect, TileType)> = Vec::new();
         let mut start_x = 0usize;
-        for m in &parsed.modules {
-            let end_x = start_x + m.x_span;
-            for obj in &m.game_objects {
-                if let Some(obj_type) = obj.get("type").and_then(|v| v.as_str()) {
-                    if obj_type.eq_ignore_ascii_case("stairs") {
-                        // stairs
-                        if let (Some(pos), Some(size_v)) = (obj.get("position"), obj.get("size")) {
-                            let x = pos.get("x").and_then(|v| v.as_u64()).unwrap_or(0) as usize + start_x;
-                            let y = pos.get("y").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                            let size = size_v.as_u64().unwrap_or(0) as usize;
-                            let orientation = obj.get("orientation").and_then(|v| v.as_i64()).unwrap_or(1) as i32;
-                            let t = tile_type_from_display_name(registry, "wall").unwrap_or(TileType::Custom("wall".into()));
-                            // Generate stairs shape similar to creation
-                            let mut cells: Vec<(usize, usize)> = Vec::new();
-                            for i in 0..size {
-                                let cx = if orientation >= 0 { x + i } else { x + size - 1 - i };
-                                let start_y = y + i;
-                                for yy in start_y..(y + size) { if cx < self.width && yy < self.height { self.tiles[yy][cx].set_tile_type(t.clone()); cells.push((cx, yy)); } }
-                            }
-                            let object_id = obj.get("objectID").and_then(|v| v.as_str()).map(|s| s.to_string());
-                            let idx = self.assign_stairs_with_cells(t.clone(), &cells, orientation);
-                            if let Some(oid) = object_id { set_meta_text_label(&mut self.stairs[idx].metadata, "Object ID", oid); }
-                            let mutable = obj.get("mutable").and_then(|v| v.as_bool()).unwrap_or(false);
-                            set_meta_bool(&mut self.stairs[idx].metadata, "mutable", mutable);
+        for (mi, m) in parsed.modules.iter().enumerate() {
+            let end_x = start_x + self.modules[mi];
+            for (oi, obj) in m.game_objects.iter().enumerate() {
+                let mut ctx = ObjectContext { report: &mut report, module_index: mi, object_index: oi };
+                let Some(obj_type) = obj.get("type").and_then(|v| v.as_str()) else {
+                    ctx.issue(IssueKind::Skipped, "missing \"type\"");
+                    continue;
+                };
+                let Some(pos) = obj.get("position") else {
+                    ctx.issue(IssueKind::Skipped, "missing \"position\"");
+                    continue;
+                };
+                let local_x = ctx.index_field(pos, "x", 0, "position.x");
+                let game_y = ctx.index_field(pos, "y", 0, "position.y");
+                if game_y >= self.height {
+                    ctx.issue(IssueKind::Skipped, format!("position.y {} is outside the level", game_y));
+                    continue;
+                }
+                // Game positions name the bottom-left cell, counting rows up from the bottom
+                let bottom = self.height - 1 - game_y;
+                if local_x >= end_x - start_x {
+                    ctx.issue(IssueKind::Skipped, format!("position.x {} is outside the module span", local_x));
+                    continue;
+                }
+                let x = start_x + local_x;
+
+                if obj_type.eq_ignore_ascii_case("stairs") {
+                    // stairs
+                    let size = match obj.get("size").and_then(|v| v.as_u64()) {
+                        Some(size) if size > 0 => usize::try_from(size).unwrap_or(usize::MAX),
+                        _ => { ctx.issue(IssueKind::Skipped, "stairs need a positive integer \"size\""); continue; }
+                    };
+                    let orientation = match obj.get("orientation").and_then(|v| v.as_i64()) {
+                        Some(o) => StairsOrientation::from_game(o),
+                        None => { ctx.issue(IssueKind::Defaulted, "missing stairs orientation, using 1"); StairsOrientation::AscendingRight }
+                    };
+                    let t = match obj.get("tileType").and_then(|v| v.as_str()) {
+                        Some(name) => tile_type_from_export_name(&registry, name).unwrap_or_else(|| {
+                            ctx.issue(IssueKind::UnknownKind, format!("unknown stairs tileType {:?}", name));
+                            TileType::Custom(name.to_string())
+                        }),
+                        None => {
+                            ctx.issue(IssueKind::Defaulted, "missing stairs tileType, using wall");
+                            tile_type_from_export_name(&registry, "wall").unwrap_or(TileType::Custom("wall".into()))
+                        }
+                    };
+                    // Stairs that don't fit are shrunk rather than cut so they keep their shape
+                    let fitted = size.min(end_x - x).min(bottom + 1);
+                    let stairs = Stairs::new(t, x, bottom + 1 - fitted, fitted, orientation, self.registry.structure_metadata(StructureKind::Stairs));
+                    // Objects never share cells in an export; keep the first one instead of breaking it
+                    if let Some(issue) = stairs.cells().into_iter().find_map(|(cx, cy)| self.overlap_at(cx, cy)) {
+                        ctx.issue(IssueKind::Skipped, issue);
+                        continue;
+                    }
+                    if fitted < size { ctx.issue(IssueKind::Clamped, format!("stairs shrunk from size {} to {} to fit the module / level", size, fitted)); }
+                    let idx = self.add_stairs(stairs);
+                    if let Some(oid) = obj.get("objectID").and_then(|v| v.as_str()) { set_meta_text(&mut self.stairs[idx].metadata, "objectID", oid.to_string()); }
+                    let mutable = obj.get("mutable").and_then(|v| v.as_bool()).unwrap_or(false);
+                    set_meta_bool(&mut self.stairs[idx].metadata, "mutable", mutable);
+                    let enabled = obj.get("enabled").and_then(|v| v.as_bool()).unwrap_or(true);
+                    set_meta_bool(&mut self.stairs[idx].metadata, "enabled", enabled);
+                } else {
+                    let t = match tile_type_from_export_name(&registry, obj_type) {
+                        Some(t) => t,
+                        None => {
+                            ctx.issue(IssueKind::UnknownKind, format!("unknown type {:?}", obj_type));
+                            TileType::Custom(obj_type.to_string())
                         }
+                    };
+                    // Distinguish platform vs tile by presence of size object
+                    if let Some(size) = obj.get("size").and_then(|v| if v.is_object() { Some(v) } else { None }) {
+                        // platform
+                        let sx = ctx.index_field(size, "x", 1, "size.x").max(1);
+                        let sy = ctx.index_field(size, "y", 1, "size.y").max(1);
+                        let max_xx = x + sx.min(end_x - x);
+                        let top = (bottom + 1).saturating_sub(sy);
+                        if (top..=bottom).any(|yy| (x..max_xx).any(|xx| self.stairs_map[yy][xx].is_some())) {
+                            ctx.issue(IssueKind::Skipped, "overlaps stairs");
+                            continue;
+                        }
+                        if sx > end_x - x || sy > bottom + 1 { ctx.issue(IssueKind::Clamped, "platform cut at the module / level edge"); }
+                        for yy in top..=bottom { for xx in x..max_xx { self.tiles[yy][xx].set_tile_type(t.clone(), &self.registry); } }
+                        // After platforms added, we will rebuild and then apply metadata
+                        placed_platforms.push((mi, oi, (x, top, max_xx - 1, bottom), t));
                     } else {
-                        // Distinguish platform vs tile by presence of size object
-                        if let Some(size) = obj.get("size").and_then(|v| if v.is_object() { Some(v) } else { None }) {
-                            // platform
-                            let type_name = obj_type;
-                            let t = tile_type_from_display_name(registry, type_name).unwrap_or(TileType::Custom(type_name.to_string()));
-                            if let Some(pos) = obj.get("position") {
-                                let px = pos.get("x").and_then(|v| v.as_u64()).unwrap_or(0) as usize + start_x;
-                                let py = pos.get("y").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                                let sx = size.get("x").and_then(|v| v.as_u64()).unwrap_or(1) as usize;
-                                let sy = size.get("y").and_then(|v| v.as_u64()).unwrap_or(1) as usize;
-                                for yy in py..py+sy { for xx in px..px+sx { if xx < self.width && yy < self.height { self.tiles[yy][xx].set_tile_type(t.clone()); } } }
-                                // After platforms added, we will rebuild and then apply metadata
-                            }
-                        } else {
-                            // single tile
-                            let type_name = obj_type;
-                            let t = tile_type_from_display_name(registry, type_name).unwrap_or(TileType::Custom(type_name.to_string()));
-                            if let Some(pos) = obj.get("position") {
-                                let x = pos.get("x").and_then(|v| v.as_u64()).unwrap_or(0) as usize + start_x;
-                                let y = pos.get("y").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                                if x < self.width && y < self.height { self.tiles[y][x].set_tile_type(t.clone());
-                                    // Apply tile metadata
-                                    if let Some(oid) = obj.get("objectID").and_then(|v| v.as_str()) { set_meta_text(&mut self.tiles[y][x].metadata, "objectID", oid.to_string()); }
-                                    if let Some(mb) = obj.get("mutable").and_then(|v| v.as_bool()) { set_meta_bool(&mut self.tiles[y][x].metadata, "mutable", mb); }
-                                }
-                            }
+                        // single tile
+                        if self.stairs_map[bottom][x].is_some() {
+                            ctx.issue(IssueKind::Skipped, "overlaps stairs");
+                            continue;
                         }
+                        self.tiles[bottom][x].set_tile_type(t.clone(), &self.registry);
+                        // Apply tile metadata
+                        if let Some(oid) = obj.get("objectID").and_then(|v| v.as_str()) { set_meta_text(&mut self.tiles[bottom][x].metadata, "objectID", oid.to_string()); }
+                        if let Some(mb) = obj.get("mutable").and_then(|v| v.as_bool()) { set_meta_bool(&mut self.tiles[bottom][x].metadata, "mutable", mb); }
+                        if let Some(eb) = obj.get("enabled").and_then(|v| v.as_bool()) { set_meta_bool(&mut self.tiles[bottom][x].metadata, "enabled", eb); }
                     }
                 }
             }
@@ -708,37 +1245,64 @@ impl Level {
 
         // Rebuild platforms then apply platform metadata
         self.rebuild_platforms();
-        // Second pass for platform metadata now that platforms exist
-        start_x = 0;
-        for m in &parsed.modules { let end_x = start_x + m.x_span; for obj in &m.game_objects {
-            if let Some(size) = obj.get("size").and_then(|v| if v.is_object() { Some(v) } else { None }) {
-                let type_name = obj.get("type").and_then(|v| v.as_str()).unwrap_or("");
-                if let Some(pos) = obj.get("position") {
-                    let px = pos.get("x").and_then(|v| v.as_u64()).unwrap_or(0) as usize + start_x;
-                    let py = pos.get("y").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                    let sx = size.get("x").and_then(|v| v.as_u64()).unwrap_or(1) as usize;
-                    let sy = size.get("y").and_then(|v| v.as_u64()).unwrap_or(1) as usize;
-                    // Find a platform that covers this rect
-                    let t_opt = tile_type_from_display_name(registry, type_name);
-                    if let Some(t_type) = t_opt {
-                        // Check top-left cell's platform
-                        if let Some(p) = self.platform_at_mut(px, py) { if p.min_x == px && p.min_y == py && (p.max_x - p.min_x + 1) == sx && (p.max_y - p.min_y + 1) == sy && p.tile_type == t_type {
-                            if let Some(oid) = obj.get("objectID").and_then(|v| v.as_str()) { set_meta_text(&mut p.metadata, "objectID", oid.to_string()); }
-                            if let Some(mb) = obj.get("mutable").and_then(|v| v.as_bool()) { set_meta_bool(&mut p.metadata, "mutable", mb); }
-                        } }
-                    }
+        for (mi, oi, (min_x, min_y, max_x, max_y), t) in placed_platforms {
+            let obj = &parsed.modules[mi].game_objects[oi];
+            let matched = match self.platform_at_mut(min_x, min_y) {
+                Some(p) if (p.min_x, p.min_y, p.max_x, p.max_y) == (min_x, min_y, max_x, max_y) && p.tile_type == t => {
+                    if let Some(oid) = obj.get("objectID").and_then(|v| v.as_str()) { set_meta_text(&mut p.metadata, "objectID", oid.to_string()); }
+                    if let Some(mb) = obj.get("mutable").and_then(|v| v.as_bool()) { set_meta_bool(&mut p.metadata, "mutable", mb); }
+                    if let Some(eb) = obj.get("enabled").and_then(|v| v.as_bool()) { set_meta_bool(&mut p.metadata, "enabled", eb); }
+                    true
                 }
-            } }
-            start_x = end_x;
+                _ => false,
+            };
+            if !matched {
+                report.push(mi, Some(oi), IssueKind::Defaulted, "platform metadata dropped: its tiles were segmented into different platforms");
+            }
         }
 
-        Ok(())
+        report
+    }
+
+    // Why an imported stairs cell can't go at (x, y), if something is already there
+    fn overlap_at(&self, x: usize, y: usize) -> Option<&'static str> {
+        if self.stairs_map[y][x].is_some() {
+            Some("overlaps stairs")
+        } else if self.tiles[y][x].tile_type != TileType::Air {
+            Some("overlaps another object")
+        } else {
+            None
+        }
     }
 }
 
-fn tile_type_from_display_name(registry: &TileRegistry, display: &str) -> Option<TileType> {
-    let dl = display.to_lowercase();
-    for k in registry.kinds() { if k.display_name.to_lowercase() == dl || k.key.to_lowercase() == dl { return Some(TileType::Custom(k.key.clone())); } }
+// Where an imported object lives, so issues can point back at it
+struct ObjectContext<'a> {
+    report: &'a mut ImportReport,
+    module_index: usize,
+    object_index: usize,
+}
+
+impl ObjectContext<'_> {
+    fn issue(&mut self, kind: IssueKind, reason: impl Into<String>) {
+        self.report.push(self.module_index, Some(self.object_index), kind, reason);
+    }
+
+    // Read a non-negative integer field, falling back to `default` (and saying so) when it's missing or malformed
+    fn index_field(&mut self, v: &serde_json::Value, key: &str, default: usize, what: &str) -> usize {
+        match v.get(key).and_then(|f| f.as_u64()) {
+            Some(n) => usize::try_from(n).unwrap_or(usize::MAX),
+            None => {
+                self.issue(IssueKind::Defaulted, format!("missing or invalid {}, using {}", what, default));
+                default
+            }
+        }
+    }
+}
+
+fn tile_type_from_export_name(registry: &TileRegistry, name: &str) -> Option<TileType> {
+    let dl = name.to_lowercase();
+    for k in registry.kinds().iter().filter(|k| k.structure.is_none()) { if k.export_name.to_lowercase() == dl || k.key.to_lowercase() == dl { return Some(TileType::Custom(k.key.clone())); } }
     None
 }
 
@@ -746,14 +1310,350 @@ fn set_meta_text(fields: &mut Vec<MetaField>, key: &str, value: String) {
     for f in fields.iter_mut() { if let MetaField::Text { key: k, value: v, .. } = f { if *k == key { *v = value.clone(); return; } } }
 }
 
-fn set_meta_text_label(fields: &mut Vec<MetaField>, label: &str, value: String) {
-    for f in fields.iter_mut() { if let MetaField::Label { label: l, value: v } = f { if *l == label { *v = value.clone(); return; } } }
-}
-
 fn set_meta_bool(fields: &mut Vec<MetaField>, key: &str, value: bool) {
     for f in fields.iter_mut() { if let MetaField::Bool { key: k, value: v, .. } = f { if *k == key { *v = value; return; } } }
 }
 
 fn set_meta_number(fields: &mut Vec<MetaField>, key: &str, value: f32) {
     for f in fields.iter_mut() { if let MetaField::Number { key: k, value: v, .. } = f { if *k == key { *v = value; return; } } }
-}
\ No newline at end of file
+}
+
+// A level with the editor's tile kinds, for tests in this module and its children
+#[cfg(test)]
+pub(crate) fn test_level(width: usize, height: usize) -> Level {
+    let manifest = crate::tile_manifest::TileManifest::load("assets/tile_types.json").unwrap();
+    let mut level = Level::new(0, height, Rc::new(TileRegistry::from_manifest_headless(&manifest)));
+    level.modules = vec![width];
+    level.apply_modules_as_width();
+    level
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(modules: serde_json::Value) -> String {
+        json!({ "formatVersion": migration::FORMAT_VERSION, "levelID": "test", "levelHeight": 10, "textureTheme": "default", "modules": modules }).to_string()
+    }
+
+    fn one_module(objects: serde_json::Value) -> String {
+        doc(json!([{ "moduleID": 0, "xSpan": 8, "gameObjects": objects }]))
+    }
+
+    fn issue_kinds(report: &ImportReport) -> Vec<IssueKind> {
+        report.issues.iter().map(|i| i.kind).collect()
+    }
+
+    #[test]
+    fn huge_module_span_is_cut_not_allocated() {
+        let json = doc(json!([{ "moduleID": 0, "xSpan": 4_000_000_000u64, "gameObjects": [] }]));
+        let mut level = test_level(8, 10);
+        let report = level.import_from_json(&json, false).unwrap();
+        assert_eq!(level.modules(), &vec![MAX_MODULE_SPAN]);
+        assert_eq!(level.width(), MAX_MODULE_SPAN);
+        assert_eq!(issue_kinds(&report), vec![IssueKind::Clamped]);
+        assert!(matches!(test_level(8, 10).import_from_json(&json, true), Err(ImportError::Rejected(_))));
+    }
+
+    #[test]
+    fn too_wide_level_is_rejected() {
+        let modules: Vec<_> = (0..MAX_LEVEL_WIDTH / MAX_MODULE_SPAN + 1).map(|i| json!({ "moduleID": i, "xSpan": MAX_MODULE_SPAN, "gameObjects": [] })).collect();
+        let mut level = test_level(8, 10);
+        assert!(matches!(level.import_from_json(&doc(json!(modules)), false), Err(ImportError::Parse(_))));
+        assert_eq!(level.width(), 8);
+    }
+
+    #[test]
+    fn huge_positions_are_skipped() {
+        let json = one_module(json!([
+            { "type": "Ground", "position": { "x": u64::MAX, "y": 0 } },
+            { "type": "Ground", "position": { "x": u64::MAX, "y": 0 }, "size": { "x": 2, "y": 1 } },
+            { "type": "stairs", "position": { "x": u64::MAX, "y": 0 }, "size": 2, "orientation": 1, "tileType": "wall" },
+            { "type": "Ground", "position": { "x": 0, "y": u64::MAX } },
+        ]));
+        let mut level = test_level(8, 10);
+        let report = level.import_from_json(&json, false).unwrap();
+        assert_eq!(issue_kinds(&report), vec![IssueKind::Skipped; 4]);
+        assert!(level.tiles.iter().flatten().all(|t| t.tile_type == TileType::Air));
+    }
+
+    #[test]
+    fn huge_sizes_are_clamped() {
+        let json = doc(json!([
+            { "moduleID": 0, "xSpan": 8, "gameObjects": [
+                { "type": "Ground", "position": { "x": 2, "y": 0 }, "size": { "x": u64::MAX, "y": u64::MAX }, "objectID": "floor" },
+            ] },
+            { "moduleID": 1, "xSpan": 4, "gameObjects": [
+                { "type": "stairs", "position": { "x": 1, "y": 0 }, "size": u64::MAX, "orientation": -1, "tileType": "wall" },
+            ] },
+        ]));
+        let mut level = test_level(8, 10);
+        let report = level.import_from_json(&json, false).unwrap();
+        assert!(issue_kinds(&report).iter().all(|&k| k == IssueKind::Clamped));
+        assert_eq!(report.issues.len(), 2);
+        assert_eq!(level.platforms.len(), 1);
+        let p = &level.platforms[0];
+        assert_eq!((p.min_x, p.min_y, p.max_x, p.max_y), (2, 0, 7, 9));
+        assert_eq!(ids::id_of(&p.metadata), Some("floor"));
+        assert_eq!(level.stairs[0].size, 3);
+    }
+
+    #[test]
+    fn objects_overlapping_stairs_are_skipped() {
+        let stairs = json!({ "type": "stairs", "position": { "x": 2, "y": 0 }, "size": 2, "orientation": 1, "tileType": "wall" });
+        let tile = json!({ "type": "Ground", "position": { "x": 3, "y": 1 } });
+        let platform = json!({ "type": "Ground", "position": { "x": 1, "y": 0 }, "size": { "x": 2, "y": 1 } });
+
+        let mut level = test_level(8, 10);
+        let report = level.import_from_json(&one_module(json!([stairs, tile, platform])), false).unwrap();
+        assert_eq!(issue_kinds(&report), vec![IssueKind::Skipped; 2]);
+        assert_eq!(level.stairs.len(), 1);
+        assert!(level.stairs[0].cells().iter().all(|&(x, y)| level.tiles[y][x].tile_type == TileType::Custom("wall".into())));
+        assert!(level.platforms.is_empty());
+
+        // The other way round the stairs are the ones left out
+        let mut level = test_level(8, 10);
+        let report = level.import_from_json(&one_module(json!([tile, platform, stairs])), false).unwrap();
+        assert_eq!(issue_kinds(&report), vec![IssueKind::Skipped]);
+        assert!(level.stairs.is_empty());
+        assert_eq!(level.tiles[8][3].tile_type, TileType::Custom("ground".into()));
+        assert_eq!((level.tiles[9][1].tile_type.clone(), level.tiles[9][2].tile_type.clone()), (custom("ground"), custom("ground")));
+    }
+
+    #[test]
+    fn fitted_platforms_keep_their_metadata() {
+        let json = one_module(json!([{ "type": "Ground", "position": { "x": 6, "y": 0 }, "size": { "x": 5 }, "objectID": "ledge" }]));
+        let mut level = test_level(8, 10);
+        let report = level.import_from_json(&json, false).unwrap();
+        assert_eq!(issue_kinds(&report), vec![IssueKind::Defaulted, IssueKind::Clamped]);
+        let p = &level.platforms[0];
+        assert_eq!((p.min_x, p.min_y, p.max_x, p.max_y), (6, 9, 7, 9));
+        assert_eq!(ids::id_of(&p.metadata), Some("ledge"));
+    }
+
+    #[test]
+    fn object_id_is_not_a_common_field() {
+        let mut level = test_level(8, 10);
+        level.set_tile(0, 0, TileType::Custom("pig".to_string()));
+        level.set_tile(2, 0, TileType::Custom("pig".to_string()));
+        let objects = [ObjectRef::Tile(0, 0), ObjectRef::Tile(2, 0)];
+        let common = level.common_metadata(&objects);
+        assert!(!common.is_empty());
+        assert!(common.iter().all(|(f, _)| f.key() != "objectID"));
+        level.generate_ids(&objects);
+        assert_eq!(level.object_id(objects[0]), Some("pig_1"));
+        assert_eq!(level.object_id(objects[1]), Some("pig_2"));
+    }
+
+    #[test]
+    fn deleted_ids_are_not_handed_out_again() {
+        let mut level = test_level(8, 10);
+        level.set_tile(0, 0, custom("pig"));
+        level.set_tile(2, 0, custom("pig"));
+        level.generate_ids(&[ObjectRef::Tile(0, 0), ObjectRef::Tile(2, 0)]);
+        level.set_tile(2, 0, TileType::Air);
+        level.finish_operation();
+        assert_eq!(level.next_object_id(ObjectRef::Tile(0, 0)), "pig_3");
+
+        // The counter outlives a save and reload
+        let mut reloaded = test_level(1, 1);
+        reloaded.load_project(&level.save_project().unwrap()).unwrap();
+        assert_eq!(reloaded.next_object_id(ObjectRef::Tile(0, 0)), "pig_4");
+    }
+
+    fn stairs_level() -> Level {
+        let mut level = test_level(8, 10);
+        level.place_stairs(TileType::Custom("wall".to_string()), 1, 0, 3, StairsOrientation::AscendingRight);
+        level.finish_operation();
+        level
+    }
+
+    fn assert_stairs_dropped(level: &Level) {
+        assert!(level.stairs.is_empty());
+        assert!(level.stairs_map.iter().flatten().all(Option::is_none));
+    }
+
+    #[test]
+    fn overwriting_a_stairs_cell_drops_the_stairs() {
+        let mut level = stairs_level();
+        let (x, y) = level.stairs[0].cells()[0];
+        level.set_tile(x, y, TileType::Custom("ground".to_string()));
+        level.finish_operation();
+        assert_stairs_dropped(&level);
+        // The untouched cells stay as plain tiles
+        assert_eq!(level.tiles.iter().flatten().filter(|t| t.tile_type == TileType::Custom("wall".to_string())).count(), 5);
+    }
+
+    #[test]
+    fn erasing_part_of_stairs_drops_the_stairs() {
+        let mut level = stairs_level();
+        level.cut_rect((3, 0, 3, 2));
+        assert_stairs_dropped(&level);
+    }
+
+    #[test]
+    fn overlapping_stairs_break_the_old_ones() {
+        let mut level = stairs_level();
+        let index = level.place_stairs(TileType::Custom("wall".to_string()), 3, 0, 2, StairsOrientation::AscendingLeft);
+        level.finish_operation();
+        assert_eq!((index, level.stairs.len()), (0, 1));
+        let cells = level.stairs[0].cells();
+        assert_eq!(level.stairs_map.iter().flatten().flatten().count(), cells.len());
+        assert!(cells.iter().all(|&(x, y)| level.stairs_map[y][x] == Some(0)));
+    }
+
+    fn custom(key: &str) -> TileType { TileType::Custom(key.to_string()) }
+
+    // Deterministic xorshift so a failing seed reproduces
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, n: usize) -> usize {
+            self.0 ^= self.0 << 13; self.0 ^= self.0 >> 7; self.0 ^= self.0 << 17;
+            (self.0 % n as u64) as usize
+        }
+        fn coin(&mut self) -> bool { self.below(2) == 0 }
+        fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str { items[self.below(items.len())] }
+    }
+
+    // One to three modules of random spans, so local positions are checked against their module's start
+    fn random_level(rng: &mut Rng) -> Level {
+        let mut level = test_level(1, 5 + rng.below(6));
+        level.modules = (0..1 + rng.below(3)).map(|_| 3 + rng.below(6)).collect();
+        level.apply_modules_as_width();
+        level
+    }
+
+    // Random id and flags on each object; ids stay unique so the import can't mix objects up
+    fn randomize_fields(level: &mut Level, rng: &mut Rng, objects: &[ObjectRef]) {
+        for (n, &object) in objects.iter().enumerate() {
+            let id = if rng.coin() { format!("obj_{}", n) } else { String::new() };
+            let (mutable, enabled) = (rng.coin(), rng.coin());
+            set_fields(level, object, &id, mutable, enabled);
+        }
+    }
+
+    // Scatter single tiles of the given kinds and give them random fields
+    fn scatter(level: &mut Level, rng: &mut Rng, kinds: &[&str]) {
+        let mut placed = Vec::new();
+        for _ in 0..level.width * level.height / 4 {
+            let (x, y) = (rng.below(level.width), rng.below(level.height));
+            level.set_tile(x, y, custom(rng.pick(kinds)));
+            placed.push(ObjectRef::Tile(x, y));
+        }
+        level.finish_operation();
+        placed.retain(|&o| matches!(o, ObjectRef::Tile(x, y) if level.tiles[y][x].tile_type != TileType::Air));
+        placed.dedup();
+        randomize_fields(level, rng, &placed);
+    }
+
+    fn set_fields(level: &mut Level, object: ObjectRef, id: &str, mutable: bool, enabled: bool) {
+        let mut fields = level.metadata_of(object).unwrap().clone();
+        set_meta_text(&mut fields, "objectID", id.to_string());
+        set_meta_bool(&mut fields, "mutable", mutable);
+        set_meta_bool(&mut fields, "enabled", enabled);
+        level.set_metadata(object, fields);
+    }
+
+    fn round_trip(level: &Level) -> Level {
+        let json = level.export_to_json("test".to_string()).unwrap();
+        let mut imported = test_level(1, 1);
+        let report = imported.import_from_json(&json, true).unwrap();
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+        assert_eq!((imported.width, imported.height, &imported.modules), (level.width, level.height, &level.modules));
+        imported
+    }
+
+    fn stairs_by_cell(level: &Level) -> Vec<Vec<Option<&Stairs>>> {
+        level.stairs_map.iter().map(|row| row.iter().map(|i| i.map(|i| &level.stairs[i])).collect()).collect()
+    }
+
+    const SEEDS: std::ops::Range<u64> = 1..150;
+    const COLLECTABLES: &[&str] = &["flagpole", "grain", "grow_powerup", "oneup", "redbull", "powerup_tile"];
+    const ENEMIES: &[&str] = &["bird", "pig", "snail", "beartrap"];
+    const PLATFORM_KINDS: &[&str] = &["ground", "grass", "wall", "ice", "mud"];
+
+    #[test]
+    fn collectables_round_trip() {
+        for seed in SEEDS {
+            let mut rng = Rng(seed);
+            let mut level = random_level(&mut rng);
+            scatter(&mut level, &mut rng, COLLECTABLES);
+            assert_eq!(round_trip(&level).tiles, level.tiles, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn enemies_round_trip() {
+        for seed in SEEDS {
+            let mut rng = Rng(seed);
+            let mut level = random_level(&mut rng);
+            scatter(&mut level, &mut rng, ENEMIES);
+            assert_eq!(round_trip(&level).tiles, level.tiles, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn platforms_round_trip() {
+        for seed in SEEDS {
+            let mut rng = Rng(seed);
+            let mut level = random_level(&mut rng);
+            // Blocks of platform kinds that may touch, overlap and cross module borders
+            for _ in 0..1 + rng.below(6) {
+                let kind = custom(rng.pick(PLATFORM_KINDS));
+                let (x, y) = (rng.below(level.width), rng.below(level.height));
+                for yy in y..(y + 1 + rng.below(3)).min(level.height) {
+                    for xx in x..(x + 1 + rng.below(5)).min(level.width) { level.set_tile(xx, yy, kind.clone()); }
+                }
+            }
+            level.finish_operation();
+            let platforms: Vec<ObjectRef> = (0..level.platforms.len()).map(ObjectRef::Platform).collect();
+            randomize_fields(&mut level, &mut rng, &platforms);
+            let imported = round_trip(&level);
+            assert_eq!(imported.tiles, level.tiles, "seed {}", seed);
+            assert_eq!(imported.platforms, level.platforms, "seed {}", seed);
+            assert_eq!(imported.platform_map, level.platform_map, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn stairs_round_trip() {
+        for seed in SEEDS {
+            let mut rng = Rng(seed);
+            let mut level = random_level(&mut rng);
+            // Stairs inside one module each; ones placed over earlier stairs break those up
+            for _ in 0..1 + rng.below(4) {
+                let module = rng.below(level.modules.len());
+                let size = 1 + rng.below(level.modules[module].min(level.height));
+                let x = level.module_start(module) + rng.below(level.modules[module] - size + 1);
+                let y = rng.below(level.height - size + 1);
+                let orientation = if rng.coin() { StairsOrientation::AscendingLeft } else { StairsOrientation::AscendingRight };
+                level.place_stairs(custom(rng.pick(PLATFORM_KINDS)), x, y, size, orientation);
+            }
+            level.finish_operation();
+            let stairs: Vec<ObjectRef> = (0..level.stairs.len()).map(ObjectRef::Stairs).collect();
+            randomize_fields(&mut level, &mut rng, &stairs);
+            let imported = round_trip(&level);
+            assert_eq!(imported.tiles, level.tiles, "seed {}", seed);
+            // The import lists stairs module by module, so compare what covers each cell
+            assert_eq!(stairs_by_cell(&imported), stairs_by_cell(&level), "seed {}", seed);
+            assert_eq!(imported.stairs.len(), level.stairs.len(), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn v1_stairs_import_a_row_higher_as_wall() {
+        let json = json!({ "formatVersion": 1, "levelID": "old", "textureTheme": "default", "modules": [{ "moduleID": 0, "xSpan": 8, "gameObjects": [
+            { "type": "stairs", "position": { "x": 2, "y": 1 }, "size": 2, "orientation": 1, "objectID": "old_steps" },
+        ] }] }).to_string();
+        let mut level = test_level(8, 10);
+        level.import_from_json(&json, true).unwrap();
+        assert_eq!(level.stairs.len(), 1);
+        let s = &level.stairs[0];
+        // v1 wrote y = 1 for stairs standing on game row 2, i.e. row 10 - 1 - 2 from the top
+        assert_eq!((s.min_x, s.max_x, s.max_y, s.size), (2, 3, 7, 2));
+        assert_eq!(s.orientation, StairsOrientation::AscendingRight);
+        assert_eq!(s.tile_type, custom("wall"));
+        assert_eq!(ids::id_of(&s.metadata), Some("old_steps"));
+    }
+}
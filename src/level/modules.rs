@@ -0,0 +1,190 @@
+use serde::{Serialize, Deserialize};
+use crate::tile::{Tile, Platform, Stairs};
+use crate::tile_type_system::MetaField;
+use crate::level::{Level, LibraryModule};
+use crate::level::history::Change;
+use crate::level::ids::set_id;
+
+// Module edits rebuild the level column by column from a new module list, so each module's
+// tiles, stairs and locked platforms travel with it instead of the right edge growing or shrinking.
+// Each entry names the old module whose columns it takes (None for a blank module) and its new span;
+// a module made narrower loses its rightmost columns, a wider one gets blank columns on the right.
+type ModuleLayout = Vec<(Option<usize>, usize)>;
+
+// What the game's module sequencer can pick modules by. Everything is optional and left out of
+// the export when unset; the theme overrides the level's textureTheme for this module only
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModuleProperties {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(rename = "textureTheme", default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    #[serde(rename = "musicCue", default, skip_serializing_if = "Option::is_none")]
+    pub music_cue: Option<String>,
+}
+
+impl Level {
+    pub fn module_start(&self, index: usize) -> usize {
+        self.modules.iter().take(index).sum()
+    }
+
+    pub fn module_properties(&self, index: usize) -> ModuleProperties {
+        self.module_properties.get(index).cloned().unwrap_or_default()
+    }
+
+    // Typing into a property field changes it every frame; merge that into one undo step per module
+    pub fn set_module_properties(&mut self, index: usize, properties: ModuleProperties) {
+        if index >= self.modules.len() || self.module_properties(index) == properties { return; }
+        self.begin_targeted_operation("Module Properties", format!("module_properties:{}", index), Change::ModuleProperties(index, self.module_properties(index)));
+        self.module_properties.resize(self.modules.len(), ModuleProperties::default());
+        self.module_properties[index] = properties;
+        self.commit_operation();
+    }
+
+    pub fn insert_module(&mut self, index: usize, span: usize) {
+        let index = index.min(self.modules.len());
+        let mut layout = self.current_layout();
+        layout.insert(index, (None, span.max(1)));
+        self.apply_layout_operation("Insert Module", layout);
+    }
+
+    pub fn delete_module(&mut self, index: usize) {
+        if index >= self.modules.len() { return; }
+        let mut layout = self.current_layout();
+        layout.remove(index);
+        self.apply_layout_operation("Delete Module", layout);
+    }
+
+    // Move module `from` so it ends up at position `to` in the list
+    pub fn move_module(&mut self, from: usize, to: usize) {
+        let count = self.modules.len();
+        if from >= count || from == to.min(count - 1) { return; }
+        let mut layout = self.current_layout();
+        let module = layout.remove(from);
+        layout.insert(to.min(count - 1), module);
+        self.apply_layout_operation("Move Module", layout);
+    }
+
+    // Insert a copy of a module right after it; the copy's objects get no objectIDs of their own
+    pub fn duplicate_module(&mut self, index: usize) {
+        if index >= self.modules.len() { return; }
+        let mut layout = self.current_layout();
+        layout.insert(index + 1, layout[index]);
+        self.apply_layout_operation("Duplicate Module", layout);
+    }
+
+    // Insert a library module as a new module at `index`. Its content stands on the bottom row;
+    // rows above the level's height are cut off. Ids already used in the level are cleared
+    pub fn insert_library_module(&mut self, index: usize, module: &LibraryModule) {
+        let index = index.min(self.modules.len());
+        let clip = module.clip.without_top_rows(module.clip.height.saturating_sub(self.height));
+        let mut layout = self.current_layout();
+        layout.insert(index, (None, clip.width.max(1)));
+        self.finish_operation();
+        self.begin_operation("Insert Library Module");
+        self.apply_layout(&layout);
+        self.module_properties[index] = module.properties.clone();
+        let (x, y) = (self.module_start(index), self.height - clip.height);
+        if clip.height > 0 {
+            self.place_clip(&clip, x, y);
+            self.clear_duplicate_ids_in((x, y, x + clip.width - 1, self.height - 1));
+        }
+        self.auto_assign_ids();
+        self.commit_operation();
+    }
+
+    // Dragging a span changes it every frame; merge that into one undo step per module
+    pub fn set_module_span(&mut self, index: usize, span: usize) {
+        if self.modules.get(index).is_none_or(|&s| s == span.max(1)) { return; }
+        let mut layout = self.current_layout();
+        layout[index].1 = span.max(1);
+        self.finish_operation();
+        self.begin_merging_operation("Module Span", format!("module_span:{}", index));
+        self.apply_layout(&layout);
+        self.auto_assign_ids();
+        self.commit_operation();
+    }
+
+    fn current_layout(&self) -> ModuleLayout {
+        self.modules.iter().enumerate().map(|(i, &span)| (Some(i), span)).collect()
+    }
+
+    fn apply_layout_operation(&mut self, description: &str, layout: ModuleLayout) {
+        self.finish_operation();
+        self.begin_operation(description);
+        self.apply_layout(&layout);
+        self.auto_assign_ids();
+        self.commit_operation();
+    }
+
+    fn apply_layout(&mut self, layout: &[(Option<usize>, usize)]) {
+        let old_starts: Vec<usize> = (0..self.modules.len()).map(|i| self.module_start(i)).collect();
+        let width: usize = layout.iter().map(|&(_, span)| span).sum();
+        let mut tiles = vec![Vec::with_capacity(width); self.height];
+        let mut stairs_map = vec![Vec::with_capacity(width); self.height];
+        let mut stairs: Vec<Stairs> = Vec::new();
+        let mut platforms: Vec<Platform> = Vec::new();
+        let mut seen = vec![false; self.modules.len()];
+
+        let mut start = 0;
+        for &(source, span) in layout {
+            let Some(m) = source else {
+                for y in 0..self.height {
+                    tiles[y].resize(start + span, Tile::default());
+                    stairs_map[y].resize(start + span, None);
+                }
+                start += span;
+                continue;
+            };
+            let (old_start, kept) = (old_starts[m], span.min(self.modules[m]));
+            let shift = |x: usize| x - old_start + start;
+            let inside = |min_x: usize, max_x: usize| min_x >= old_start && max_x < old_start + kept;
+
+            // Structures that still fit come along. Platforms are re-segmented afterwards, but locked
+            // ones are kept and the rest pass their metadata on to the platforms that replace them
+            let mut stairs_index = vec![None; self.stairs.len()];
+            for (i, s) in self.stairs.iter().enumerate().filter(|(_, s)| inside(s.min_x, s.max_x)) {
+                stairs_index[i] = Some(stairs.len());
+                let mut s = Stairs { min_x: shift(s.min_x), max_x: shift(s.max_x), ..s.clone() };
+                if seen[m] { clear_id(&mut s.metadata); }
+                stairs.push(s);
+            }
+            for p in self.platforms.iter().filter(|p| inside(p.min_x, p.max_x)) {
+                let mut p = Platform { min_x: shift(p.min_x), max_x: shift(p.max_x), ..p.clone() };
+                if seen[m] { clear_id(&mut p.metadata); }
+                platforms.push(p);
+            }
+            for y in 0..self.height {
+                for x in old_start..old_start + kept {
+                    let mut tile = self.tiles[y][x].clone();
+                    if seen[m] { clear_id(&mut tile.metadata); }
+                    tiles[y].push(tile);
+                    stairs_map[y].push(self.stairs_map[y][x].and_then(|i| stairs_index[i]));
+                }
+                tiles[y].resize(start + span, Tile::default());
+                stairs_map[y].resize(start + span, None);
+            }
+            seen[m] = true;
+            start += span;
+        }
+
+        self.width = width;
+        self.module_properties = layout.iter().map(|&(source, _)| source.map(|m| self.module_properties(m)).unwrap_or_default()).collect();
+        self.modules = layout.iter().map(|&(_, span)| span).collect();
+        self.tiles = tiles;
+        self.stairs = stairs;
+        self.stairs_map = stairs_map;
+        self.platforms = platforms;
+        self.platform_map = vec![vec![None; width]; self.height];
+        self.compact_stairs();
+        self.rebuild_platforms();
+    }
+}
+
+fn clear_id(fields: &mut [MetaField]) {
+    set_id(fields, String::new());
+}
@@ -1,69 +1,86 @@
-use crate::tile::{TileType, default_platform_metadata_for, PlatformGroup};
+use crate::tile::{TileType, PlatformGroup};
+use crate::tile_manifest::StructureKind;
 use crate::level::{Level, Platform};
+use crate::level::ids::{id_of, set_id};
+use crate::level::history::Change;
+use std::cmp::Reverse;
+use std::collections::HashSet;
 
 impl Level {
     pub(crate) fn rebuild_platforms(&mut self) {
-        self.platforms.clear();
+        // Platforms as they are now and as they were when the pending operation began, so ids
+        // lost to local updates during a stroke can still be recovered
+        let mut previous = std::mem::take(&mut self.platforms);
+        let previous_current = previous.len();
+        if let Some(Change::Snapshot(before)) = self.current_operation.as_ref().map(|op| &op.before) { previous.extend(before.platforms.iter().cloned()); }
         for row in &mut self.platform_map { for cell in row.iter_mut() { *cell = None; } }
 
         let mut visited = vec![vec![false; self.width]; self.height];
 
-        for y in 0..self.height {
-            for x in 0..self.width {
-                if visited[y][x] { continue; }
-                // seed tile
-                // Only tiles belonging to a platform group join platforms, ignore stairs, and respect module borders
-                if self.stairs_map[y][x].is_some() { continue; }
-                let group = self.registry_platform_group_for(&self.tiles[y][x].tile_type);
-                if group.is_none() { continue; }
-                let group = group.unwrap();
-                let module_end_x = self.module_end_for_x(x);
-
-                // First row width from (x,y)
-                let mut max_width = 0usize;
-                let mut xi = x;
-                while xi < self.width && xi < module_end_x {
-                    if !visited[y][xi]
-                        && self.stairs_map[y][xi].is_none()
-                        && self.registry_platform_group_for(&self.tiles[y][xi].tile_type) == Some(group) {
-                        max_width += 1;
-                        xi += 1;
-                    } else { break; }
-                }
-                if max_width == 0 { continue; }
-
-                let mut rect_width = max_width;
-                let mut rect_height = 1usize;
-                let mut yy = y + 1;
-                while yy < self.height {
-                    let mut run = 0usize;
-                    let mut xx = x;
-                    while xx < x + rect_width && xx < self.width && xx < module_end_x {
-                        if !visited[yy][xx]
-                            && self.stairs_map[yy][xx].is_none()
-                            && self.registry_platform_group_for(&self.tiles[yy][xx].tile_type) == Some(group) { run += 1; xx += 1; } else { break; }
-                    }
-                    if run == 0 { break; }
-                    rect_width = rect_width.min(run);
-                    rect_height += 1;
-                    yy += 1;
-                }
+        // Locked rectangles come first; the automatic segmentation only covers the tiles they leave
+        let mut locked: Vec<Platform> = previous.iter().take(previous_current).filter(|p| p.locked).cloned().collect();
+        locked.retain(|p| {
+            let keep = self.locked_rect_is_valid(p, &visited);
+            if keep { for row in &mut visited[p.min_y..=p.max_y] { row[p.min_x..=p.max_x].fill(true); } }
+            keep
+        });
+        for p in locked {
+            let index = self.platforms.len();
+            for ty in p.min_y..=p.max_y { for tx in p.min_x..=p.max_x { self.platform_map[ty][tx] = Some(index); } }
+            self.platforms.push(p);
+        }
+        let first_auto = self.platforms.len();
 
-                let min_x = x;
-                let min_y = y;
-                let max_x = x + rect_width - 1;
-                let max_y = y + rect_height - 1;
+        for (min_x, min_y, max_x, max_y) in self.auto_platform_rects(self.segmentation, &visited) {
+            let platform_index = self.platforms.len();
+            for ty in min_y..=max_y { for tx in min_x..=max_x { self.platform_map[ty][tx] = Some(platform_index); } }
+            self.platforms.push(Platform { tile_type: self.tiles[min_y][min_x].tile_type.clone(), min_x, min_y, max_x, max_y, metadata: self.registry.structure_metadata(StructureKind::Platform), locked: false });
+        }
+        self.inherit_platform_metadata(first_auto..self.platforms.len(), &previous);
+    }
+
+    // A locked platform survives while its cells are all of its group, free of stairs, inside
+    // one module and not claimed by another locked platform
+    fn locked_rect_is_valid(&self, p: &Platform, claimed: &[Vec<bool>]) -> bool {
+        let Some(group) = self.registry.platform_group_for(&p.tile_type) else { return false };
+        if p.max_x >= self.width || p.max_y >= self.height || p.max_x >= self.module_end_for_x(p.min_x) { return false; }
+        (p.min_y..=p.max_y).all(|ty| (p.min_x..=p.max_x).all(|tx| {
+            !claimed[ty][tx] && self.stairs_map[ty][tx].is_none() && self.registry.platform_group_for(&self.tiles[ty][tx].tile_type) == Some(group)
+        }))
+    }
 
-                let platform_index = self.platforms.len();
-                for ty in min_y..=max_y { for tx in min_x..=max_x { visited[ty][tx] = true; self.platform_map[ty][tx] = Some(platform_index); } }
-                self.platforms.push(Platform { tile_type: self.tiles[y][x].tile_type.clone(), min_x, min_y, max_x, max_y, metadata: default_platform_metadata_for(self.tiles[y][x].tile_type.clone()) });
+    // Re-segmenting replaces platform objects. Each new platform takes the metadata of the old one
+    // it came from: same bounds first, otherwise the one of its group it overlaps most. When a
+    // platform splits every piece keeps the fields, but only the strongest match keeps the objectID
+    pub(crate) fn inherit_platform_metadata(&mut self, targets: std::ops::Range<usize>, previous: &[Platform]) {
+        let mut taken: HashSet<String> = self.platforms.iter().enumerate()
+            .filter(|(i, _)| !targets.contains(i))
+            .filter_map(|(_, p)| id_of(&p.metadata).filter(|id| !id.is_empty()).map(str::to_string))
+            .collect();
+        let group = |t: &TileType| self.registry.platform_group_for(t);
+        let mut matches: Vec<(usize, (bool, usize), &Platform)> = targets.clone().filter_map(|i| {
+            let p = &self.platforms[i];
+            previous.iter()
+                .filter(|old| group(&old.tile_type) == group(&p.tile_type))
+                .map(|old| ((bounds(old) == bounds(p), overlap(old, p)), old))
+                .filter(|&((_, area), _)| area > 0)
+                .max_by_key(|&(score, _)| score)
+                .map(|(score, old)| (i, score, old))
+        }).collect();
+        matches.sort_by_key(|m| Reverse(m.1));
+        for (i, _, old) in matches {
+            let mut metadata = old.metadata.clone();
+            if let Some(id) = id_of(&metadata).filter(|id| !id.is_empty()).map(str::to_string)
+                && !taken.insert(id) {
+                set_id(&mut metadata, String::new());
             }
+            self.platforms[i].metadata = metadata;
         }
     }
 
     pub(crate) fn try_update_platforms_locally(&mut self, x: usize, y: usize) {
         let t = self.tiles[y][x].tile_type.clone();
-        let group = self.registry_platform_group_for(&t);
+        let group = self.registry.platform_group_for(&t);
         if group.is_none() { return; }
         let group = group.unwrap();
 
@@ -73,13 +90,14 @@ impl Level {
             let nx = x as isize + ox; let ny = y as isize + oy;
             if nx >= 0 && ny >= 0 {
                 let nxu = nx as usize; let nyu = ny as usize;
-                if nxu < self.width && nyu < self.height {
-                    if let Some(idx) = self.platform_map[nyu][nxu] { if self.platforms[idx].tile_type == t { neighbor_indices.insert(idx); } }
+                if nxu < self.width && nyu < self.height
+                    && let Some(idx) = self.platform_map[nyu][nxu] && self.platforms[idx].tile_type == t {
+                    neighbor_indices.insert(idx);
                 }
             }
         };
         add_idx(-1, 0); add_idx(1, 0); add_idx(0, -1); add_idx(0, 1);
-        if let Some(idx) = self.platform_map[y][x] { if self.registry_platform_group_for(&self.platforms[idx].tile_type) == Some(group) { neighbor_indices.insert(idx); } }
+        if let Some(idx) = self.platform_map[y][x] && self.registry.platform_group_for(&self.platforms[idx].tile_type) == Some(group) { neighbor_indices.insert(idx); }
 
         let mut merged_rect: Option<(usize, usize, usize, usize)> = None;
         if !neighbor_indices.is_empty() {
@@ -89,42 +107,53 @@ impl Level {
         }
 
         let target_rect = merged_rect.unwrap_or_else(|| self.maximal_rect_including_group(x, y, group));
-        self.remove_platforms_overlapping_rect_of_group(target_rect, group);
+        // Locked platforms are only changed by hand; leave the cell to the rebuild at the end of the operation
+        if self.rect_touches_locked(target_rect) { return; }
+        let removed = self.remove_platforms_overlapping_rect_of_group(target_rect, group);
         self.assign_platform_rect(self.tiles[y][x].tile_type.clone(), target_rect);
+        let new_index = self.platforms.len() - 1;
+        self.inherit_platform_metadata(new_index..new_index + 1, &removed);
     }
 
-    // removed: is_platform_type; grouping is handled via registry_platform_group_for
+    pub(crate) fn rect_touches_locked(&self, rect: (usize, usize, usize, usize)) -> bool {
+        let (min_x, min_y, max_x, max_y) = rect;
+        (min_y..=max_y).any(|ty| (min_x..=max_x).any(|tx| self.platform_map[ty][tx].is_some_and(|i| self.platforms[i].locked)))
+    }
+
+    // removed: is_platform_type; grouping comes from the registry's platform groups
 
     pub(crate) fn rect_is_uniform_group(&self, min_x: usize, min_y: usize, max_x: usize, max_y: usize, group: PlatformGroup) -> bool {
-        for yy in min_y..=max_y { for xx in min_x..=max_x { if self.registry_platform_group_for(&self.tiles[yy][xx].tile_type) != Some(group) { return false; } } }
+        for yy in min_y..=max_y { for xx in min_x..=max_x { if self.registry.platform_group_for(&self.tiles[yy][xx].tile_type) != Some(group) { return false; } } }
         true
     }
 
     pub(crate) fn maximal_rect_including_group(&self, x: usize, y: usize, group: PlatformGroup) -> (usize, usize, usize, usize) {
-        let mut left = x; while left > 0 && self.registry_platform_group_for(&self.tiles[y][left - 1].tile_type) == Some(group) { left -= 1; }
-        let mut right = x; while right + 1 < self.width && self.registry_platform_group_for(&self.tiles[y][right + 1].tile_type) == Some(group) { right += 1; }
+        let mut left = x; while left > 0 && self.registry.platform_group_for(&self.tiles[y][left - 1].tile_type) == Some(group) { left -= 1; }
+        let mut right = x; while right + 1 < self.width && self.registry.platform_group_for(&self.tiles[y][right + 1].tile_type) == Some(group) { right += 1; }
         let mut top = y; while top > 0 {
-            let nt = top - 1; let mut ok = true; for xx in left..=right { if self.registry_platform_group_for(&self.tiles[nt][xx].tile_type) != Some(group) { ok = false; break; } }
+            let nt = top - 1; let mut ok = true; for xx in left..=right { if self.registry.platform_group_for(&self.tiles[nt][xx].tile_type) != Some(group) { ok = false; break; } }
             if ok { top = nt; } else { break; }
         }
         let mut bottom = y; while bottom + 1 < self.height {
-            let nb = bottom + 1; let mut ok = true; for xx in left..=right { if self.registry_platform_group_for(&self.tiles[nb][xx].tile_type) != Some(group) { ok = false; break; } }
+            let nb = bottom + 1; let mut ok = true; for xx in left..=right { if self.registry.platform_group_for(&self.tiles[nb][xx].tile_type) != Some(group) { ok = false; break; } }
             if ok { bottom = nb; } else { break; }
         }
         (left, top, right, bottom)
     }
 
-    pub(crate) fn remove_platforms_overlapping_rect_of_group(&mut self, rect: (usize, usize, usize, usize), group: PlatformGroup) {
+    // Returns the removed platforms so their metadata can be handed on
+    pub(crate) fn remove_platforms_overlapping_rect_of_group(&mut self, rect: (usize, usize, usize, usize), group: PlatformGroup) -> Vec<Platform> {
         use std::collections::BTreeSet;
         let (min_x, min_y, max_x, max_y) = rect;
         let mut to_remove: BTreeSet<usize> = BTreeSet::new();
-        for yy in min_y..=max_y { for xx in min_x..=max_x { if let Some(idx) = self.platform_map[yy][xx] { if self.registry_platform_group_for(&self.platforms[idx].tile_type) == Some(group) { to_remove.insert(idx); } } } }
-        if to_remove.is_empty() { return; }
+        for yy in min_y..=max_y { for xx in min_x..=max_x { if let Some(idx) = self.platform_map[yy][xx] { if self.registry.platform_group_for(&self.platforms[idx].tile_type) == Some(group) { to_remove.insert(idx); } } } }
+        if to_remove.is_empty() { return Vec::new(); }
 
+        let mut removed = Vec::with_capacity(to_remove.len());
         let mut new_platforms: Vec<Platform> = Vec::with_capacity(self.platforms.len() - to_remove.len());
         let mut old_to_new: Vec<Option<usize>> = vec![None; self.platforms.len()];
         for (old_idx, plat) in self.platforms.iter().enumerate() {
-            if to_remove.contains(&old_idx) { continue; }
+            if to_remove.contains(&old_idx) { removed.push(plat.clone()); continue; }
             let new_idx = new_platforms.len(); new_platforms.push(plat.clone()); old_to_new[old_idx] = Some(new_idx);
         }
 
@@ -133,28 +162,204 @@ impl Level {
             else if let Some(new_idx) = old_to_new[old_idx] { self.platform_map[yy][xx] = Some(new_idx); }
         } } }
         self.platforms = new_platforms;
+        removed
     }
 
     pub(crate) fn assign_platform_rect(&mut self, t: TileType, rect: (usize, usize, usize, usize)) {
         let (min_x, min_y, max_x, max_y) = rect; let new_index = self.platforms.len();
-        self.platforms.push(Platform { tile_type: t.clone(), min_x, min_y, max_x, max_y, metadata: default_platform_metadata_for(t) });
+        self.platforms.push(Platform { tile_type: t.clone(), min_x, min_y, max_x, max_y, metadata: self.registry.structure_metadata(StructureKind::Platform), locked: false });
         for yy in min_y..=max_y { for xx in min_x..=max_x { self.platform_map[yy][xx] = Some(new_index); } }
     }
 
-    fn registry_platform_group_for(&self, tile_type: &TileType) -> Option<PlatformGroup> {
-        // For now infer by key (same criteria as registry); later, use a shared registry instance
-        match tile_type {
-            TileType::Air => None,
-            TileType::Custom(k) => {
-                let kl = k.to_lowercase();
-                if kl.contains("ground") { Some(PlatformGroup::Ground) }
-                else if kl.contains("wall") { Some(PlatformGroup::Wall) }
-                else if kl.contains("grass") { Some(PlatformGroup::Grass) }
-                else if kl.contains("ice") { Some(PlatformGroup::Ice) }
-                else if kl.contains("mud") { Some(PlatformGroup::Mud) }
-                else { None }
-            }
-        }
+    // Make the rectangle one locked platform, e.g. after the platform brush drew it. Platforms it
+    // overlaps are dropped and hand their metadata to it. Call inside an operation
+    pub(crate) fn lock_platform_rect(&mut self, tile_type: TileType, rect: (usize, usize, usize, usize)) {
+        let (min_x, min_y, max_x, max_y) = rect;
+        let candidate = Platform { tile_type, min_x, min_y, max_x, max_y, metadata: self.registry.structure_metadata(StructureKind::Platform), locked: true };
+        let (removed, kept): (Vec<Platform>, Vec<Platform>) = std::mem::take(&mut self.platforms).into_iter().partition(|p| overlap(p, &candidate) > 0);
+        self.platforms = kept;
+        self.platforms.push(candidate);
+        let index = self.platforms.len() - 1;
+        self.inherit_platform_metadata(index..index + 1, &removed);
+        self.rebuild_platforms();
+    }
+
+    pub fn set_platform_locked(&mut self, index: usize, locked: bool) {
+        if self.platforms.get(index).is_none_or(|p| p.locked == locked) { return; }
+        self.finish_operation();
+        self.begin_operation(if locked { "Lock Platform" } else { "Unlock Platform" });
+        self.platforms[index].locked = locked;
+        self.rebuild_platforms();
+        self.commit_operation();
+    }
+
+    // Cut a platform in two along the left (vertical) or top edge of cell (x, y). Both halves are
+    // locked so the auto-segmenter doesn't join them again; the second half gets no objectID
+    pub fn split_platform(&mut self, index: usize, x: usize, y: usize, vertical: bool) -> bool {
+        let Some(p) = self.platforms.get(index).cloned() else { return false };
+        let (first, second) = if vertical {
+            if x <= p.min_x || x > p.max_x { return false; }
+            (Platform { max_x: x - 1, ..p.clone() }, Platform { min_x: x, ..p.clone() })
+        } else {
+            if y <= p.min_y || y > p.max_y { return false; }
+            (Platform { max_y: y - 1, ..p.clone() }, Platform { min_y: y, ..p.clone() })
+        };
+        self.finish_operation();
+        self.begin_operation("Split Platform");
+        let mut second = Platform { locked: true, ..second };
+        set_id(&mut second.metadata, String::new());
+        self.platforms[index] = Platform { locked: true, ..first };
+        self.platforms.push(second);
+        self.rebuild_platforms();
+        self.commit_operation();
+        true
+    }
+
+    // Platforms touched by the rectangle, if there are several of one group that together cover
+    // exactly their bounding box
+    pub fn mergeable_platforms(&self, rect: (usize, usize, usize, usize)) -> Option<Vec<usize>> {
+        let (min_x, min_y, max_x, max_y) = rect;
+        let area = Platform { tile_type: TileType::Air, min_x, min_y, max_x, max_y, metadata: Vec::new(), locked: false };
+        let indices: Vec<usize> = (0..self.platforms.len()).filter(|&i| overlap(&self.platforms[i], &area) > 0).collect();
+        if indices.len() < 2 { return None; }
+        let group = self.registry.platform_group_for(&self.platforms[indices[0]].tile_type);
+        if indices.iter().any(|&i| self.registry.platform_group_for(&self.platforms[i].tile_type) != group) { return None; }
+        let merged = merged_bounds(indices.iter().map(|&i| &self.platforms[i]));
+        if self.module_index_for_x(merged.0) != self.module_index_for_x(merged.2) { return None; }
+        let covered: usize = indices.iter().map(|&i| self.platforms[i].width() * self.platforms[i].height()).sum();
+        (covered == (merged.2 - merged.0 + 1) * (merged.3 - merged.1 + 1)).then_some(indices)
+    }
+
+    // Join the platforms into one locked platform that keeps the largest one's metadata
+    pub fn merge_platforms(&mut self, rect: (usize, usize, usize, usize)) -> bool {
+        let Some(indices) = self.mergeable_platforms(rect) else { return false };
+        self.finish_operation();
+        self.begin_operation("Merge Platforms");
+        let parts: Vec<Platform> = indices.iter().map(|&i| self.platforms[i].clone()).collect();
+        let (min_x, min_y, max_x, max_y) = merged_bounds(parts.iter());
+        let largest = parts.iter().max_by_key(|p| p.width() * p.height()).cloned().unwrap();
+        let mut index = 0;
+        self.platforms.retain(|_| { index += 1; !indices.contains(&(index - 1)) });
+        self.platforms.push(Platform { min_x, min_y, max_x, max_y, locked: true, ..largest });
+        self.rebuild_platforms();
+        self.commit_operation();
+        true
     }
 }
 
+fn bounds(p: &Platform) -> (usize, usize, usize, usize) { (p.min_x, p.min_y, p.max_x, p.max_y) }
+
+fn overlap(a: &Platform, b: &Platform) -> usize {
+    let w = (a.max_x.min(b.max_x) + 1).saturating_sub(a.min_x.max(b.min_x));
+    let h = (a.max_y.min(b.max_y) + 1).saturating_sub(a.min_y.max(b.min_y));
+    w * h
+}
+
+fn merged_bounds<'a>(platforms: impl Iterator<Item = &'a Platform>) -> (usize, usize, usize, usize) {
+    platforms.fold((usize::MAX, usize::MAX, 0, 0), |(a, b, c, d), p| (a.min(p.min_x), b.min(p.min_y), c.max(p.max_x), d.max(p.max_y)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::level::{test_level, get_meta_bool, set_meta_bool, ObjectRef};
+
+    fn wall() -> TileType { TileType::Custom("wall".to_string()) }
+
+    fn paint(level: &mut Level, cells: &[(usize, usize)], t: TileType) {
+        for &(x, y) in cells { level.set_tile(x, y, t.clone()); }
+        level.finish_operation();
+    }
+
+    fn row(x0: usize, x1: usize, y: usize) -> Vec<(usize, usize)> { (x0..=x1).map(|x| (x, y)).collect() }
+
+    // Give the platform covering (x, y) an id and a non-default mutable flag
+    fn tag(level: &mut Level, x: usize, y: usize, id: &str) {
+        let index = level.platform_map[y][x].unwrap();
+        let mut fields = level.platforms[index].metadata.clone();
+        set_id(&mut fields, id.to_string());
+        set_meta_bool(&mut fields, "mutable", true);
+        level.set_metadata(ObjectRef::Platform(index), fields);
+    }
+
+    fn ids(level: &Level) -> Vec<&str> {
+        let mut ids: Vec<&str> = level.platforms.iter().filter_map(|p| id_of(&p.metadata)).filter(|id| !id.is_empty()).collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn fields_survive_an_unrelated_paint() {
+        let mut level = test_level(10, 10);
+        paint(&mut level, &row(0, 3, 0), wall());
+        tag(&mut level, 0, 0, "floor");
+        paint(&mut level, &[(8, 5)], wall());
+        paint(&mut level, &[(6, 9)], TileType::Custom("pig".to_string()));
+        let p = &level.platforms[level.platform_map[0][0].unwrap()];
+        assert_eq!(bounds(p), (0, 0, 3, 0));
+        assert_eq!(id_of(&p.metadata), Some("floor"));
+        assert!(get_meta_bool(&p.metadata, "mutable", false));
+    }
+
+    #[test]
+    fn merge_keeps_the_stronger_overlaps_id() {
+        let mut level = test_level(10, 10);
+        paint(&mut level, &row(0, 2, 0), wall());
+        paint(&mut level, &row(4, 5, 0), wall());
+        tag(&mut level, 0, 0, "long");
+        tag(&mut level, 4, 0, "short");
+        paint(&mut level, &[(3, 0)], wall());
+        assert_eq!(level.platforms.len(), 1);
+        assert_eq!(bounds(&level.platforms[0]), (0, 0, 5, 0));
+        assert_eq!(ids(&level), vec!["long"]);
+    }
+
+    #[test]
+    fn split_pieces_keep_fields_but_one_keeps_the_id() {
+        let mut level = test_level(10, 10);
+        paint(&mut level, &row(0, 4, 0), wall());
+        tag(&mut level, 0, 0, "floor");
+        paint(&mut level, &[(2, 0)], TileType::Air);
+        assert_eq!(level.platforms.len(), 2);
+        assert!(level.platforms.iter().all(|p| get_meta_bool(&p.metadata, "mutable", false)));
+        assert_eq!(ids(&level), vec!["floor"]);
+    }
+
+    #[test]
+    fn locked_platforms_survive_a_rebuild() {
+        let mut level = test_level(10, 10);
+        let block: Vec<(usize, usize)> = (0..2).flat_map(|y| row(0, 3, y)).collect();
+        paint(&mut level, &block, wall());
+        level.begin_operation("Lock");
+        level.lock_platform_rect(wall(), (0, 0, 1, 1));
+        level.finish_operation();
+        tag(&mut level, 0, 0, "pillar");
+        paint(&mut level, &row(0, 3, 2), wall());
+        paint(&mut level, &[(8, 8)], wall());
+        let p = &level.platforms[level.platform_map[0][0].unwrap()];
+        assert!(p.locked);
+        assert_eq!(bounds(p), (0, 0, 1, 1));
+        assert_eq!(id_of(&p.metadata), Some("pillar"));
+        let lock = level.platform_map[0][0];
+        assert!(row(0, 3, 2).into_iter().chain(row(2, 3, 0)).all(|(x, y)| level.platform_map[y][x].is_some_and(|i| Some(i) != lock)));
+    }
+
+    #[test]
+    fn manifest_groups_need_no_builtin_name() {
+        let manifest: crate::tile_manifest::TileManifest = serde_json::from_value(serde_json::json!({ "tileTypes": [
+            { "id": "lava", "displayName": "Lava", "category": "Tiles", "platformGroup": "Magma" },
+            { "id": "crust", "displayName": "Crust", "category": "Tiles", "platformGroup": "Magma" },
+            { "id": "cloud", "displayName": "Cloud", "category": "Tiles", "platformGroup": "Sky" },
+        ] })).unwrap();
+        let mut level = Level::new(0, 4, std::rc::Rc::new(crate::tile::TileRegistry::from_manifest_headless(&manifest)));
+        level.modules = vec![6];
+        level.apply_modules_as_width();
+        let kind = |k: &str| TileType::Custom(k.to_string());
+        paint(&mut level, &row(0, 1, 0), kind("lava"));
+        paint(&mut level, &row(2, 3, 0), kind("crust"));
+        paint(&mut level, &row(4, 5, 0), kind("cloud"));
+        assert_eq!(level.platforms.len(), 2);
+        assert_eq!(bounds(&level.platforms[level.platform_map[0][0].unwrap()]), (0, 0, 3, 0));
+        assert_eq!(bounds(&level.platforms[level.platform_map[0][4].unwrap()]), (4, 0, 5, 0));
+    }
+}
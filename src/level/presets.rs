@@ -0,0 +1,41 @@
+use serde::{Serialize, Deserialize};
+use crate::tile::TileType;
+use crate::tile_type_system::MetaField;
+use crate::level::{Level, set_id};
+
+// Named metadata values for one tile kind. The drawing brush can stamp a preset onto every tile it
+// places instead of the kind's defaults. Presets belong to the project, not to the exported level.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MetadataPreset {
+    pub name: String,
+    pub tile_type: TileType,
+    pub fields: Vec<MetaField>,
+}
+
+impl Level {
+    pub fn presets(&self) -> &[MetadataPreset] { &self.presets }
+
+    // Presets of one kind, with their index into presets()
+    pub fn presets_for(&self, tile_type: &TileType) -> Vec<(usize, &MetadataPreset)> {
+        self.presets.iter().enumerate().filter(|(_, p)| p.tile_type == *tile_type).collect()
+    }
+
+    // Saving under an existing name of the same kind overwrites it. Object ids are per object,
+    // so they are never part of a preset
+    pub fn save_preset(&mut self, name: &str, tile_type: TileType, mut fields: Vec<MetaField>) -> usize {
+        set_id(&mut fields, String::new());
+        self.mark_modified();
+        let preset = MetadataPreset { name: name.to_string(), tile_type, fields };
+        match self.presets.iter().position(|p| p.name == preset.name && p.tile_type == preset.tile_type) {
+            Some(i) => { self.presets[i] = preset; i }
+            None => { self.presets.push(preset); self.presets.len() - 1 }
+        }
+    }
+
+    pub fn remove_preset(&mut self, index: usize) {
+        if index < self.presets.len() {
+            self.mark_modified();
+            self.presets.remove(index);
+        }
+    }
+}
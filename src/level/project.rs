@@ -0,0 +1,196 @@
+use std::collections::BTreeMap;
+use serde::{Serialize, Deserialize};
+use serde::de::Error as _;
+use crate::tile::{Tile, Platform, Stairs, StairsOrientation};
+use crate::tile_type_system::MetaField;
+use crate::level::{Level, MetadataPreset, Segmentation, ModuleProperties, DEFAULT_THEME, MAX_LEVEL_WIDTH, MAX_LEVEL_HEIGHT};
+
+// Editor-native project file. Unlike the game export (LevelData) this keeps every tile,
+// structure, module span and metadata field, so a saved work-in-progress reopens unchanged.
+pub const PROJECT_EXTENSION: &str = "psel";
+const PROJECT_VERSION: u32 = 2; // v2: stairs carry size and orientation instead of an "Orientation" label
+
+#[derive(Serialize, Deserialize)]
+struct ProjectData {
+    version: u32,
+    width: usize,
+    height: usize,
+    modules: Vec<usize>,
+    #[serde(default)]
+    module_properties: Vec<ModuleProperties>,
+    #[serde(default = "default_theme")]
+    theme: String,
+    tiles: Vec<Vec<Tile>>,
+    platforms: Vec<Platform>,
+    platform_map: Vec<Vec<Option<usize>>>,
+    stairs: Vec<Stairs>,
+    stairs_map: Vec<Vec<Option<usize>>>,
+    #[serde(default)]
+    presets: Vec<MetadataPreset>,
+    #[serde(default)]
+    auto_ids: bool,
+    #[serde(default)]
+    id_counters: BTreeMap<String, u32>,
+    #[serde(default)]
+    segmentation: Segmentation,
+}
+
+impl Level {
+    pub fn save_project(&self) -> serde_json::Result<String> {
+        let data = ProjectData {
+            version: PROJECT_VERSION,
+            width: self.width,
+            height: self.height,
+            modules: self.modules.clone(),
+            module_properties: self.module_properties.clone(),
+            theme: self.theme.clone(),
+            tiles: self.tiles.clone(),
+            platforms: self.platforms.clone(),
+            platform_map: self.platform_map.clone(),
+            stairs: self.stairs.clone(),
+            stairs_map: self.stairs_map.clone(),
+            presets: self.presets.clone(),
+            auto_ids: self.auto_ids,
+            id_counters: self.id_counters.clone(),
+            segmentation: self.segmentation,
+        };
+        serde_json::to_string_pretty(&data)
+    }
+
+    pub fn load_project(&mut self, json_str: &str) -> serde_json::Result<()> {
+        let mut data: ProjectData = serde_json::from_str(json_str)?;
+        if data.version > PROJECT_VERSION {
+            return Err(serde_json::Error::custom(format!("project version {} is newer than supported version {}", data.version, PROJECT_VERSION)));
+        }
+
+        // Reject files whose grids don't agree with the stored dimensions instead of panicking later
+        if data.width > MAX_LEVEL_WIDTH || data.height > MAX_LEVEL_HEIGHT {
+            return Err(serde_json::Error::custom(format!("project is {}x{}, larger than {}x{}", data.width, data.height, MAX_LEVEL_WIDTH, MAX_LEVEL_HEIGHT)));
+        }
+        if data.modules.iter().try_fold(0usize, |sum, &span| sum.checked_add(span)) != Some(data.width) {
+            return Err(serde_json::Error::custom("project modules do not add up to its width"));
+        }
+        if !grid_matches(&data.tiles, data.width, data.height)
+            || !grid_matches(&data.platform_map, data.width, data.height)
+            || !grid_matches(&data.stairs_map, data.width, data.height) {
+            return Err(serde_json::Error::custom("project grid does not match its width/height"));
+        }
+        if data.platform_map.iter().flatten().flatten().any(|&i| i >= data.platforms.len())
+            || data.stairs_map.iter().flatten().flatten().any(|&i| i >= data.stairs.len()) {
+            return Err(serde_json::Error::custom("project references a missing platform or stairs"));
+        }
+        let in_grid = |min_x: usize, min_y: usize, max_x: usize, max_y: usize| {
+            min_x <= max_x && min_y <= max_y && max_x < data.width && max_y < data.height
+        };
+        if !data.platforms.iter().all(|p| in_grid(p.min_x, p.min_y, p.max_x, p.max_y))
+            || !data.stairs.iter().all(|s| in_grid(s.min_x, s.min_y, s.max_x, s.max_y)) {
+            return Err(serde_json::Error::custom("project has a platform or stairs outside the level"));
+        }
+        if data.version < 2 { data.stairs.iter_mut().for_each(upgrade_v1_stairs); }
+        if !data.stairs.iter().all(|s| s.size >= 1 && s.max_x - s.min_x + 1 == s.size && s.max_y - s.min_y + 1 == s.size) {
+            return Err(serde_json::Error::custom("project has stairs whose size does not match their bounds"));
+        }
+
+        self.width = data.width;
+        self.height = data.height;
+        self.modules = data.modules;
+        self.module_properties = data.module_properties;
+        self.module_properties.resize(self.modules.len(), ModuleProperties::default());
+        self.theme = data.theme;
+        self.tiles = data.tiles;
+        self.platforms = data.platforms;
+        self.platform_map = data.platform_map;
+        self.stairs = data.stairs;
+        self.stairs_map = data.stairs_map;
+        self.presets = data.presets;
+        self.auto_ids = data.auto_ids;
+        self.id_counters = data.id_counters;
+        self.segmentation = data.segmentation;
+
+        // A freshly opened project starts with a clean slate
+        self.current_operation = None;
+        self.highlighted_tiles.clear();
+        self.history.clear();
+        self.mark_saved();
+        Ok(())
+    }
+}
+
+fn default_theme() -> String {
+    DEFAULT_THEME.to_string()
+}
+
+fn grid_matches<T>(grid: &[Vec<T>], width: usize, height: usize) -> bool {
+    grid.len() == height && grid.iter().all(|row| row.len() == width)
+}
+
+fn upgrade_v1_stairs(stairs: &mut Stairs) {
+    stairs.size = (stairs.max_x - stairs.min_x + 1).max(stairs.max_y - stairs.min_y + 1);
+    let label = stairs.metadata.iter().position(|f| matches!(f, MetaField::Label { label, .. } if label == "Orientation"));
+    if let Some(i) = label
+        && let MetaField::Label { value, .. } = stairs.metadata.remove(i) {
+        stairs.orientation = StairsOrientation::from_game(value.trim().parse().unwrap_or(-1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::level::{test_level, ObjectRef};
+    use crate::tile::TileType;
+
+    fn custom(key: &str) -> TileType { TileType::Custom(key.to_string()) }
+
+    fn sample_level() -> Level {
+        let mut level = test_level(6, 8);
+        level.modules = vec![6, 4];
+        level.apply_modules_as_width();
+        for x in 0..10 { level.set_tile(x, 7, custom("ground")); }
+        for x in 2..5 { level.set_tile(x, 3, custom("ice")); }
+        level.set_tile(1, 6, custom("pig"));
+        level.set_tile(8, 2, custom("oneup"));
+        level.place_stairs(custom("wall"), 6, 4, 3, StairsOrientation::AscendingLeft);
+        level.finish_operation();
+        level.generate_ids(&[ObjectRef::Tile(1, 6), ObjectRef::Platform(level.platform_map[3][2].unwrap())]);
+        level.set_module_properties(1, ModuleProperties { name: "end".to_string(), difficulty: Some(2), ..Default::default() });
+        level.set_theme("cave");
+        level.save_preset("fast", custom("pig"), level.tiles[6][1].metadata.clone());
+        level.set_segmentation(Segmentation::Optimal);
+        level.auto_ids = true;
+        level.finish_operation();
+        level
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let level = sample_level();
+        let saved = level.save_project().unwrap();
+        let mut loaded = test_level(1, 1);
+        loaded.load_project(&saved).unwrap();
+        assert_eq!(loaded.capture_state(), level.capture_state());
+        assert_eq!(loaded.presets, level.presets);
+        assert_eq!((loaded.auto_ids, &loaded.id_counters), (level.auto_ids, &level.id_counters));
+        assert_eq!(loaded.save_project().unwrap(), saved);
+        assert!(!loaded.is_dirty());
+    }
+
+    fn load_edited(edit: impl FnOnce(&mut serde_json::Value)) -> serde_json::Result<()> {
+        let mut doc: serde_json::Value = serde_json::from_str(&sample_level().save_project().unwrap()).unwrap();
+        edit(&mut doc);
+        test_level(1, 1).load_project(&doc.to_string())
+    }
+
+    #[test]
+    fn inconsistent_files_are_rejected() {
+        assert!(load_edited(|_| {}).is_ok());
+        assert!(load_edited(|d| d["modules"][1] = 5.into()).is_err());
+        assert!(load_edited(|d| d["modules"][1] = u64::MAX.into()).is_err());
+        assert!(load_edited(|d| d["platforms"][0]["max_x"] = 10.into()).is_err());
+        assert!(load_edited(|d| d["platforms"][0]["min_y"] = 9.into()).is_err());
+        assert!(load_edited(|d| d["stairs"][0]["max_y"] = 8.into()).is_err());
+        assert!(load_edited(|d| d["stairs"][0]["size"] = 5.into()).is_err());
+        assert!(load_edited(|d| d["stairs"][0]["size"] = 0.into()).is_err());
+        // v1 stairs are sized from their bounds, which must not be inverted
+        assert!(load_edited(|d| { d["version"] = 1.into(); d["stairs"][0]["min_x"] = 9.into(); }).is_err());
+    }
+}
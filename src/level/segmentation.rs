@@ -0,0 +1,512 @@
+use std::collections::VecDeque;
+use serde::{Serialize, Deserialize};
+use crate::level::{Level, CellRect};
+
+// How unlocked platform tiles are cut into platform rectangles. Greedy is the original top-left
+// scan; Optimal finds the fewest rectangles for each platform group within each module, which
+// means fewer game objects and colliders for the same terrain.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Segmentation {
+    #[default]
+    Greedy,
+    Optimal,
+}
+
+impl Segmentation {
+    pub const ALL: [Segmentation; 2] = [Segmentation::Greedy, Segmentation::Optimal];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Segmentation::Greedy => "Greedy",
+            Segmentation::Optimal => "Optimal",
+        }
+    }
+}
+
+impl Level {
+    pub fn segmentation(&self) -> Segmentation { self.segmentation }
+
+    // Switch the level's segmentation and re-segment right away, as one undoable step
+    pub fn set_segmentation(&mut self, segmentation: Segmentation) {
+        if self.segmentation == segmentation { return; }
+        self.finish_operation();
+        self.begin_operation("Change Segmentation");
+        self.segmentation = segmentation;
+        self.rebuild_platforms();
+        self.commit_operation();
+    }
+
+    // Rectangles covering every platform tile not already claimed (by locked platforms)
+    pub(crate) fn auto_platform_rects(&self, segmentation: Segmentation, claimed: &[Vec<bool>]) -> Vec<CellRect> {
+        match segmentation {
+            Segmentation::Greedy => self.greedy_rects(claimed.to_vec()),
+            Segmentation::Optimal => self.optimal_rects(claimed),
+        }
+    }
+
+    // How many automatic platforms the level would get with the given segmentation
+    pub fn auto_platform_count(&self, segmentation: Segmentation) -> usize {
+        let mut claimed = vec![vec![false; self.width]; self.height];
+        for p in self.platforms.iter().filter(|p| p.locked) {
+            for row in &mut claimed[p.min_y..=p.max_y] { for cell in &mut row[p.min_x..=p.max_x] { *cell = true; } }
+        }
+        self.auto_platform_rects(segmentation, &claimed).len()
+    }
+
+    fn greedy_rects(&self, mut visited: Vec<Vec<bool>>) -> Vec<CellRect> {
+        let mut rects = Vec::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if visited[y][x] { continue; }
+                // seed tile
+                // Only tiles belonging to a platform group join platforms, ignore stairs, and respect module borders
+                if self.stairs_map[y][x].is_some() { continue; }
+                let group = self.registry.platform_group_for(&self.tiles[y][x].tile_type);
+                if group.is_none() { continue; }
+                let group = group.unwrap();
+                let module_end_x = self.module_end_for_x(x);
+
+                // First row width from (x,y)
+                let mut max_width = 0usize;
+                let mut xi = x;
+                while xi < self.width && xi < module_end_x {
+                    if !visited[y][xi]
+                        && self.stairs_map[y][xi].is_none()
+                        && self.registry.platform_group_for(&self.tiles[y][xi].tile_type) == Some(group) {
+                        max_width += 1;
+                        xi += 1;
+                    } else { break; }
+                }
+                if max_width == 0 { continue; }
+
+                let mut rect_width = max_width;
+                let mut rect_height = 1usize;
+                let mut yy = y + 1;
+                while yy < self.height {
+                    let mut run = 0usize;
+                    let mut xx = x;
+                    while xx < x + rect_width && xx < self.width && xx < module_end_x {
+                        if !visited[yy][xx]
+                            && self.stairs_map[yy][xx].is_none()
+                            && self.registry.platform_group_for(&self.tiles[yy][xx].tile_type) == Some(group) { run += 1; xx += 1; } else { break; }
+                    }
+                    if run == 0 { break; }
+                    rect_width = rect_width.min(run);
+                    rect_height += 1;
+                    yy += 1;
+                }
+
+                let (max_x, max_y) = (x + rect_width - 1, y + rect_height - 1);
+                for row in &mut visited[y..=max_y] { for cell in &mut row[x..=max_x] { *cell = true; } }
+                rects.push((x, y, max_x, max_y));
+            }
+        }
+        rects
+    }
+
+    // Minimum partition of each group's tiles, one module at a time
+    fn optimal_rects(&self, claimed: &[Vec<bool>]) -> Vec<CellRect> {
+        let mut groups = Vec::new();
+        for row in &self.tiles {
+            for tile in row {
+                if let Some(g) = self.registry.platform_group_for(&tile.tile_type) && !groups.contains(&g) {
+                    groups.push(g);
+                }
+            }
+        }
+
+        let mut borders = self.module_borders();
+        if borders.first() != Some(&0) { borders.insert(0, 0); }
+        borders.push(self.width);
+        let mut rects = Vec::new();
+        for span in borders.windows(2) {
+            let (start, end) = (span[0], span[1].min(self.width));
+            if start >= end { continue; }
+            for &group in &groups {
+                let mask: Vec<Vec<bool>> = (0..self.height).map(|y| (start..end).map(|x| {
+                    !claimed[y][x] && self.stairs_map[y][x].is_none() && self.registry.platform_group_for(&self.tiles[y][x].tile_type) == Some(group)
+                }).collect()).collect();
+                rects.extend(min_rect_partition(&mask).into_iter().map(|(a, b, c, d)| (a + start, b, c + start, d)));
+            }
+        }
+        // Same order as the greedy scan so platform indices read top-left to bottom-right
+        rects.sort_by_key(|&(x, y, _, _)| (y, x));
+        rects
+    }
+}
+
+// Fewest rectangles covering the true cells of a grid (rows of cells, mask[y][x]).
+//
+// Classic construction for rectilinear polygons, holes included: every reflex corner needs a cut.
+// Chords joining two reflex corners settle both at once, so take the largest set of chords that
+// don't touch each other (maximum independent set of the bipartite horizontal/vertical crossing
+// graph, via König's theorem), then cut from each corner still unsettled until the cut hits the
+// boundary or an earlier cut. The pieces left are the rectangles.
+pub(crate) fn min_rect_partition(mask: &[Vec<bool>]) -> Vec<CellRect> {
+    let height = mask.len();
+    let width = mask.first().map_or(0, |r| r.len());
+    let cell = |x: isize, y: isize| x >= 0 && y >= 0 && (x as usize) < width && (y as usize) < height && mask[y as usize][x as usize];
+    // Lattice segments with region cells on both sides
+    let h_inner = |vx: usize, vy: usize| cell(vx as isize, vy as isize - 1) && cell(vx as isize, vy as isize);
+    let v_inner = |vx: usize, vy: usize| cell(vx as isize - 1, vy as isize) && cell(vx as isize, vy as isize);
+
+    // Reflex corners: lattice points with exactly three of their four cells inside
+    let mut reflex = vec![vec![false; width + 1]; height + 1];
+    let mut corners = Vec::new();
+    for (vy, row) in reflex.iter_mut().enumerate() {
+        for (vx, is_reflex) in row.iter_mut().enumerate() {
+            let (x, y) = (vx as isize, vy as isize);
+            let inside = [cell(x - 1, y - 1), cell(x, y - 1), cell(x - 1, y), cell(x, y)].iter().filter(|&&c| c).count();
+            if inside == 3 { *is_reflex = true; corners.push((vx, vy)); }
+        }
+    }
+
+    // Chords between consecutive reflex corners on a line, running through the interior
+    let mut h_chords: Vec<(usize, usize, usize)> = Vec::new(); // (vy, x0, x1)
+    let mut v_chords: Vec<(usize, usize, usize)> = Vec::new(); // (vx, y0, y1)
+    for &(vx, vy) in &corners {
+        let mut x = vx;
+        while x < width && h_inner(x, vy) {
+            x += 1;
+            if reflex[vy][x] { h_chords.push((vy, vx, x)); break; }
+        }
+        let mut y = vy;
+        while y < height && v_inner(vx, y) {
+            y += 1;
+            if reflex[y][vx] { v_chords.push((vx, vy, y)); break; }
+        }
+    }
+
+    // Horizontal and vertical chords conflict when they share any point, endpoints included
+    let crosses = |h: &(usize, usize, usize), v: &(usize, usize, usize)| (h.1..=h.2).contains(&v.0) && (v.1..=v.2).contains(&h.0);
+    // Vertical chords per column, top to bottom (corners are visited row by row), so each
+    // horizontal chord only looks up the few that can reach its row in the columns it spans
+    let mut v_by_column = vec![Vec::new(); width + 1];
+    for (j, &(vx, _, _)) in v_chords.iter().enumerate() { v_by_column[vx].push(j); }
+    let adjacency: Vec<Vec<usize>> = h_chords.iter().map(|h| {
+        let mut crossing = Vec::new();
+        for column in &v_by_column[h.1..=h.2] {
+            let first = column.partition_point(|&j| v_chords[j].2 < h.0);
+            crossing.extend(column[first..].iter().copied().take_while(|&j| crosses(h, &v_chords[j])));
+        }
+        crossing
+    }).collect();
+    let (match_h, match_v) = max_matching(&adjacency, v_chords.len());
+
+    // König: walk alternating paths from unmatched horizontal chords; the independent set is the
+    // visited horizontals plus the unvisited verticals
+    let mut seen_h = vec![false; h_chords.len()];
+    let mut seen_v = vec![false; v_chords.len()];
+    let mut stack: Vec<usize> = (0..h_chords.len()).filter(|&i| match_h[i].is_none()).collect();
+    for &i in &stack { seen_h[i] = true; }
+    while let Some(i) = stack.pop() {
+        for &j in &adjacency[i] {
+            if seen_v[j] { continue; }
+            seen_v[j] = true;
+            if let Some(k) = match_v[j] && !seen_h[k] { seen_h[k] = true; stack.push(k); }
+        }
+    }
+
+    // Cuts on lattice segments: h_cut[vy][x] runs (x, vy)-(x+1, vy), v_cut[y][vx] runs (vx, y)-(vx, y+1)
+    let mut h_cut = vec![vec![false; width]; height + 1];
+    let mut v_cut = vec![vec![false; width + 1]; height];
+    let mut touched = vec![vec![false; width + 1]; height + 1];
+    for (i, &(vy, x0, x1)) in h_chords.iter().enumerate() {
+        if !seen_h[i] { continue; }
+        h_cut[vy][x0..x1].fill(true);
+        touched[vy][x0..=x1].fill(true);
+    }
+    for (j, &(vx, y0, y1)) in v_chords.iter().enumerate() {
+        if seen_v[j] { continue; }
+        for row in &mut v_cut[y0..y1] { row[vx] = true; }
+        for row in &mut touched[y0..=y1] { row[vx] = true; }
+    }
+
+    // Settle the remaining corners with a horizontal cut into the interior
+    for &(vx, vy) in &corners {
+        if touched[vy][vx] { continue; }
+        touched[vy][vx] = true;
+        if vx < width && h_inner(vx, vy) {
+            let mut x = vx;
+            while x < width && h_inner(x, vy) {
+                h_cut[vy][x] = true;
+                x += 1;
+                if touched[vy][x] { break; }
+                touched[vy][x] = true;
+            }
+        } else {
+            let mut x = vx;
+            while x > 0 && h_inner(x - 1, vy) {
+                h_cut[vy][x - 1] = true;
+                x -= 1;
+                if touched[vy][x] { break; }
+                touched[vy][x] = true;
+            }
+        }
+    }
+
+    // Each piece between cuts is a rectangle
+    let mut done = vec![vec![false; width]; height];
+    let mut rects = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            if !mask[y][x] || done[y][x] { continue; }
+            let (mut min_x, mut min_y, mut max_x, mut max_y) = (x, y, x, y);
+            let mut stack = vec![(x, y)];
+            done[y][x] = true;
+            while let Some((cx, cy)) = stack.pop() {
+                min_x = min_x.min(cx); min_y = min_y.min(cy); max_x = max_x.max(cx); max_y = max_y.max(cy);
+                let mut visit = |nx: usize, ny: usize, stack: &mut Vec<(usize, usize)>| {
+                    if mask[ny][nx] && !done[ny][nx] { done[ny][nx] = true; stack.push((nx, ny)); }
+                };
+                if cx + 1 < width && !v_cut[cy][cx + 1] { visit(cx + 1, cy, &mut stack); }
+                if cx > 0 && !v_cut[cy][cx] { visit(cx - 1, cy, &mut stack); }
+                if cy + 1 < height && !h_cut[cy + 1][cx] { visit(cx, cy + 1, &mut stack); }
+                if cy > 0 && !h_cut[cy][cx] { visit(cx, cy - 1, &mut stack); }
+            }
+            rects.push((min_x, min_y, max_x, max_y));
+        }
+    }
+    rects
+}
+
+// Maximum bipartite matching (Hopcroft-Karp); returns the partner of each left and right node.
+// Augmenting paths can run through a large share of the chords on big noisy levels, so both the
+// layering and the path search keep their own queue and stack instead of recursing
+fn max_matching(adjacency: &[Vec<usize>], right_count: usize) -> (Vec<Option<usize>>, Vec<Option<usize>>) {
+    let mut match_l = vec![None; adjacency.len()];
+    let mut match_r: Vec<Option<usize>> = vec![None; right_count];
+    let mut layer = vec![usize::MAX; adjacency.len()];
+    loop {
+        // Layer left nodes by their distance from a free left node along alternating paths
+        let mut queue = VecDeque::new();
+        for (i, partner) in match_l.iter().enumerate() {
+            layer[i] = if partner.is_none() { queue.push_back(i); 0 } else { usize::MAX };
+        }
+        let mut reachable_free = false;
+        while let Some(i) = queue.pop_front() {
+            for &j in &adjacency[i] {
+                match match_r[j] {
+                    None => reachable_free = true,
+                    Some(k) if layer[k] == usize::MAX => { layer[k] = layer[i] + 1; queue.push_back(k); }
+                    Some(_) => {}
+                }
+            }
+        }
+        if !reachable_free { break; }
+
+        // Augment along the layers from each free left node. `next[i]` is the edge of i being
+        // tried; nodes that lead nowhere leave the layering for the rest of this phase
+        let mut next = vec![0; adjacency.len()];
+        for root in 0..adjacency.len() {
+            if match_l[root].is_some() { continue; }
+            let mut path = vec![root];
+            while let Some(&i) = path.last() {
+                let Some(&j) = adjacency[i].get(next[i]) else {
+                    layer[i] = usize::MAX;
+                    path.pop();
+                    if let Some(&parent) = path.last() { next[parent] += 1; }
+                    continue;
+                };
+                match match_r[j] {
+                    None => {
+                        for &l in &path {
+                            let r = adjacency[l][next[l]];
+                            match_l[l] = Some(r);
+                            match_r[r] = Some(l);
+                        }
+                        break;
+                    }
+                    Some(k) if layer[k] == layer[i] + 1 => path.push(k),
+                    Some(_) => next[i] += 1,
+                }
+            }
+        }
+    }
+    (match_l, match_r)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::level::{test_level, MAX_LEVEL_WIDTH, MAX_LEVEL_HEIGHT};
+    use crate::tile::TileType;
+
+    fn mask(rows: &[&str]) -> Vec<Vec<bool>> {
+        rows.iter().map(|r| r.chars().map(|c| c == '#').collect()).collect()
+    }
+
+    // Deterministic noise so failures reproduce
+    fn random_mask(width: usize, height: usize, seed: u64) -> Vec<Vec<bool>> {
+        let mut state = seed;
+        (0..height).map(|_| (0..width).map(|_| {
+            state ^= state << 13; state ^= state >> 7; state ^= state << 17;
+            !state.is_multiple_of(3)
+        }).collect()).collect()
+    }
+
+    fn assert_exact_cover(mask: &[Vec<bool>], rects: &[CellRect]) {
+        let mut covered = vec![vec![0; mask[0].len()]; mask.len()];
+        for &(min_x, min_y, max_x, max_y) in rects {
+            for row in &mut covered[min_y..=max_y] { for c in &mut row[min_x..=max_x] { *c += 1; } }
+        }
+        for (y, row) in mask.iter().enumerate() {
+            for (x, &inside) in row.iter().enumerate() {
+                assert_eq!(covered[y][x], usize::from(inside), "cell ({}, {})", x, y);
+            }
+        }
+    }
+
+    // Platform counts the level gets for the mask painted in wall tiles, greedy then optimal
+    fn counts(mask: &[Vec<bool>]) -> (usize, usize) {
+        let mut level = test_level(mask[0].len(), mask.len());
+        for (y, row) in mask.iter().enumerate() {
+            for (x, _) in row.iter().enumerate().filter(|(_, inside)| **inside) {
+                level.set_tile(x, y, TileType::Custom("wall".to_string()));
+            }
+        }
+        level.finish_operation();
+        (level.auto_platform_count(Segmentation::Greedy), level.auto_platform_count(Segmentation::Optimal))
+    }
+
+    #[test]
+    fn known_shapes_get_the_fewest_rectangles() {
+        let shapes: [(&[&str], usize); 6] = [
+            (&["####", "####"], 1),
+            (&["#..", "#..", "###"], 2),
+            (&["#.#", "#.#", "###"], 3),
+            (&[".#.", "###", ".#."], 3),
+            (&["###", "#.#", "###"], 4),
+            (&["#####", "#.#.#", "#####"], 5),
+        ];
+        for (rows, expected) in shapes {
+            let m = mask(rows);
+            let rects = min_rect_partition(&m);
+            assert_exact_cover(&m, &rects);
+            assert_eq!(rects.len(), expected, "{:?}", rows);
+        }
+    }
+
+    #[test]
+    fn random_masks_are_covered_exactly() {
+        for seed in 1..200 {
+            let m = random_mask(9, 7, seed);
+            assert_exact_cover(&m, &min_rect_partition(&m));
+        }
+    }
+
+    #[test]
+    fn optimal_never_needs_more_than_greedy() {
+        let (mut greedy_total, mut optimal_total) = (0, 0);
+        for seed in 1..100 {
+            let (greedy, optimal) = counts(&random_mask(8, 6, seed));
+            assert!(optimal <= greedy, "seed {}: optimal {} > greedy {}", seed, optimal, greedy);
+            greedy_total += greedy;
+            optimal_total += optimal;
+        }
+        assert!(optimal_total < greedy_total);
+    }
+
+    // Named layouts, two modules wide, with the platform count each segmentation gives per module
+    // as (greedy, optimal). Optimal cuts each module on its own, so no module may get worse
+    type Fixture = (&'static str, &'static [&'static str], &'static [(usize, usize)]);
+    const FIXTURES: &[Fixture] = &[
+        ("steps", &[
+            "#.......|#.......",
+            "##......|##......",
+            "###.....|###.....",
+            "####....|####....",
+        ], &[(4, 4), (4, 4)]),
+        ("comb", &[
+            "#.#.#.#.|########",
+            "#.#.#.#.|#.#.#.#.",
+            "########|#.#.#.#.",
+        ], &[(8, 5), (5, 5)]),
+        ("ring", &[
+            "########|..####..",
+            "#......#|..#..#..",
+            "#......#|..####..",
+            "########|........",
+        ], &[(4, 4), (4, 4)]),
+        ("plus", &[
+            "...##...|##....##",
+            "########|########",
+            "...##...|##....##",
+        ], &[(3, 3), (3, 3)]),
+        ("zigzag", &[
+            "###.....|.######.",
+            ".###....|.##..##.",
+            "..###...|.######.",
+            "...###..|........",
+        ], &[(4, 4), (4, 4)]),
+    ];
+
+    #[test]
+    fn fixtures_per_module() {
+        for &(name, rows, expected) in FIXTURES {
+            let m: Vec<Vec<bool>> = rows.iter().map(|r| r.chars().filter(|&c| c != '|').map(|c| c == '#').collect()).collect();
+            let mut level = test_level(m[0].len(), m.len());
+            level.modules = rows[0].split('|').map(str::len).collect();
+            level.apply_modules_as_width();
+            for (y, row) in m.iter().enumerate() {
+                for (x, _) in row.iter().enumerate().filter(|(_, inside)| **inside) {
+                    level.set_tile(x, y, TileType::Custom("wall".to_string()));
+                }
+            }
+            level.finish_operation();
+            let claimed = vec![vec![false; level.width]; level.height];
+            let per_module = |segmentation| {
+                let mut counts = vec![0; level.modules.len()];
+                for (min_x, _, max_x, _) in level.auto_platform_rects(segmentation, &claimed) {
+                    let module = level.module_index_for_x(min_x).unwrap();
+                    assert_eq!(level.module_index_for_x(max_x), Some(module), "{}: rectangle crosses a module border", name);
+                    counts[module] += 1;
+                }
+                counts
+            };
+            let counts: Vec<(usize, usize)> = per_module(Segmentation::Greedy).into_iter().zip(per_module(Segmentation::Optimal)).collect();
+            assert!(counts.iter().all(|(greedy, optimal)| optimal <= greedy), "{}: {:?}", name, counts);
+            assert_eq!(counts, expected, "{}", name);
+        }
+    }
+
+    // A tall noisy module is the shape that once overflowed the stack while matching chords
+    #[test]
+    fn large_noisy_grid_is_partitioned() {
+        let m = random_mask(300, 300, 7);
+        assert_exact_cover(&m, &min_rect_partition(&m));
+    }
+
+    // Left i prefers right i + 1, so the last left node can only be matched by shifting every
+    // earlier one back along a single augmenting path through the whole graph
+    fn assert_long_path_matched(n: usize) {
+        let adjacency: Vec<Vec<usize>> = (0..n).map(|i| if i + 1 < n { vec![i + 1, i] } else { vec![i] }).collect();
+        let (match_l, match_r) = max_matching(&adjacency, n);
+        assert!(match_l.iter().enumerate().all(|(i, &j)| j == Some(i)));
+        assert!(match_r.iter().all(Option::is_some));
+    }
+
+    #[test]
+    fn long_augmenting_paths_are_followed() {
+        assert_long_path_matched(10_000);
+    }
+
+    #[test]
+    #[ignore]
+    fn million_node_augmenting_path() {
+        assert_long_path_matched(1_000_000);
+    }
+
+    // Benchmark on the largest grid a level can have:
+    // cargo test --release segmentation_benchmark -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn segmentation_benchmark() {
+        let m = random_mask(MAX_LEVEL_WIDTH, MAX_LEVEL_HEIGHT, 7);
+        let started = std::time::Instant::now();
+        let rects = min_rect_partition(&m);
+        let elapsed = started.elapsed();
+        assert_exact_cover(&m, &rects);
+        println!("{}x{} noisy mask: {} rectangles in {:?}", MAX_LEVEL_WIDTH, MAX_LEVEL_HEIGHT, rects.len(), elapsed);
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::level::{Level, ObjectRef};
+use crate::tile::{TileType, TileRegistry};
+use crate::tile_type_system::MetaField;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning, // Suspicious, export still allowed
+    Error,   // Export is blocked until fixed
+}
+
+impl Severity {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub rule: &'static str,
+    pub message: String,
+    pub cell: Option<(usize, usize)>, // (x, y) of the offending cell, None for level-wide problems
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.cell {
+            Some((x, y)) => write!(f, "{} [{}] at ({}, {}): {}", self.severity.name(), self.rule, x, y, self.message),
+            None => write!(f, "{} [{}]: {}", self.severity.name(), self.rule, self.message),
+        }
+    }
+}
+
+// A single lint check over the whole level
+pub trait Rule {
+    fn name(&self) -> &'static str;
+    fn check(&self, level: &Level, out: &mut Vec<Diagnostic>);
+}
+
+// Which kinds are required, need ground or have required fields comes from the tile manifest
+pub fn default_rules(registry: &TileRegistry) -> Vec<Box<dyn Rule>> {
+    let kinds = registry.kinds();
+    let mut rules: Vec<Box<dyn Rule>> = vec![Box::new(DuplicateObjectIds)];
+    for kind in kinds.iter().filter(|k| k.required_in_level) {
+        rules.push(Box::new(RequiredKind { kind: kind.key.clone() }));
+    }
+    rules.push(Box::new(FloatingEnemies { enemy_kinds: kinds.iter().filter(|k| k.needs_ground).map(|k| k.key.clone()).collect() }));
+    rules.push(Box::new(RequiredText { fields: kinds.iter().flat_map(|k| k.required_fields.iter().map(|f| (k.key.clone(), f.clone()))).collect() }));
+    rules.push(Box::new(ModuleLayout));
+    rules
+}
+
+// Run every rule; errors come first, then by position
+pub fn run_rules(level: &Level, rules: &[Box<dyn Rule>]) -> Vec<Diagnostic> {
+    let mut out = Vec::new();
+    for rule in rules {
+        rule.check(level, &mut out);
+    }
+    out.sort_by(|a, b| b.severity.cmp(&a.severity).then(a.cell.cmp(&b.cell)));
+    out
+}
+
+impl Level {
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        run_rules(self, &default_rules(&self.registry))
+    }
+
+    // Objects that end up in the export: platforms, stairs and tiles outside any structure
+    pub(crate) fn exported_objects(&self) -> Vec<(ObjectRef, (usize, usize))> {
+        let mut objects = Vec::new();
+        for (i, p) in self.platforms.iter().enumerate() { objects.push((ObjectRef::Platform(i), (p.min_x, p.min_y))); }
+        for (i, s) in self.stairs.iter().enumerate() { objects.push((ObjectRef::Stairs(i), (s.min_x, s.min_y))); }
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.platform_map[y][x].is_none() && self.stairs_map[y][x].is_none() && self.tiles[y][x].tile_type != TileType::Air {
+                    objects.push((ObjectRef::Tile(x, y), (x, y)));
+                }
+            }
+        }
+        objects
+    }
+}
+
+fn meta_text<'a>(fields: &'a [MetaField], key: &str) -> Option<&'a str> {
+    fields.iter().find_map(|f| match f {
+        MetaField::Text { key: k, value, .. } if k == key => Some(value.as_str()),
+        _ => None,
+    })
+}
+
+fn kind_at(level: &Level, x: usize, y: usize) -> Option<&str> {
+    match &level.tiles[y][x].tile_type {
+        TileType::Custom(k) => Some(k.as_str()),
+        TileType::Air => None,
+    }
+}
+
+// Every non-empty objectID must be unique across exported objects
+pub struct DuplicateObjectIds;
+
+impl Rule for DuplicateObjectIds {
+    fn name(&self) -> &'static str { "duplicate-object-id" }
+
+    fn check(&self, level: &Level, out: &mut Vec<Diagnostic>) {
+        let mut first_seen: HashMap<String, (usize, usize)> = HashMap::new();
+        for (object, cell) in level.exported_objects() {
+            let Some(id) = level.metadata_of(object).and_then(|m| meta_text(m, "objectID")) else { continue };
+            if id.is_empty() { continue; }
+            match first_seen.get(id) {
+                Some(&(fx, fy)) => out.push(Diagnostic {
+                    severity: Severity::Error,
+                    rule: self.name(),
+                    message: format!("objectID {:?} is already used at ({}, {})", id, fx, fy),
+                    cell: Some(cell),
+                }),
+                None => { first_seen.insert(id.to_string(), cell); }
+            }
+        }
+    }
+}
+
+// The level must contain at least one tile of the given kind (e.g. the goal flagpole)
+pub struct RequiredKind {
+    pub kind: String,
+}
+
+impl Rule for RequiredKind {
+    fn name(&self) -> &'static str { "required-kind" }
+
+    fn check(&self, level: &Level, out: &mut Vec<Diagnostic>) {
+        let present = (0..level.height).any(|y| (0..level.width).any(|x| kind_at(level, x, y) == Some(self.kind.as_str())));
+        if !present {
+            out.push(Diagnostic { severity: Severity::Error, rule: self.name(), message: format!("level has no {}", self.kind), cell: None });
+        }
+    }
+}
+
+// Walking enemies need solid ground directly below them: a platform kind or a stairs cell
+pub struct FloatingEnemies {
+    pub enemy_kinds: Vec<String>,
+}
+
+impl Rule for FloatingEnemies {
+    fn name(&self) -> &'static str { "floating-enemy" }
+
+    fn check(&self, level: &Level, out: &mut Vec<Diagnostic>) {
+        for y in 0..level.height {
+            for x in 0..level.width {
+                let Some(kind) = kind_at(level, x, y) else { continue };
+                if !self.enemy_kinds.iter().any(|k| k == kind) { continue; }
+                let grounded = y + 1 < level.height && is_solid(level, x, y + 1);
+                if !grounded {
+                    out.push(Diagnostic { severity: Severity::Warning, rule: self.name(), message: format!("{} has nothing to stand on", kind), cell: Some((x, y)) });
+                }
+            }
+        }
+    }
+}
+
+fn is_solid(level: &Level, x: usize, y: usize) -> bool {
+    level.stairs_map[y][x].is_some() || level.registry.platform_group_for(&level.tiles[y][x].tile_type).is_some()
+}
+
+// Text fields that must not be left empty on objects of a kind (e.g. a powerup's collectableClass)
+pub struct RequiredText {
+    pub fields: Vec<(String, String)>, // (kind, field key)
+}
+
+impl Rule for RequiredText {
+    fn name(&self) -> &'static str { "required-text" }
+
+    fn check(&self, level: &Level, out: &mut Vec<Diagnostic>) {
+        for (object, cell) in level.exported_objects() {
+            let (Some(TileType::Custom(kind)), Some(fields)) = (level.tile_type_of(object), level.metadata_of(object)) else { continue };
+            for (_, key) in self.fields.iter().filter(|(k, _)| k == kind) {
+                if meta_text(fields, key).is_some_and(|v| v.trim().is_empty()) {
+                    out.push(Diagnostic { severity: Severity::Error, rule: self.name(), message: format!("{} is empty", key), cell: Some(cell) });
+                }
+            }
+        }
+    }
+}
+
+// Modules must cover the level exactly and structures must stay inside one module
+pub struct ModuleLayout;
+
+impl Rule for ModuleLayout {
+    fn name(&self) -> &'static str { "module-layout" }
+
+    fn check(&self, level: &Level, out: &mut Vec<Diagnostic>) {
+        let total: usize = level.modules.iter().sum();
+        if total != level.width {
+            out.push(Diagnostic { severity: Severity::Error, rule: self.name(), message: format!("module spans add up to {} but the level is {} wide", total, level.width), cell: None });
+        }
+        for p in &level.platforms {
+            if level.module_index_for_x(p.min_x) != level.module_index_for_x(p.max_x) {
+                out.push(Diagnostic { severity: Severity::Error, rule: self.name(), message: "platform crosses a module border".to_string(), cell: Some((p.min_x, p.min_y)) });
+            }
+        }
+        for s in &level.stairs {
+            if level.module_index_for_x(s.min_x) != level.module_index_for_x(s.max_x) {
+                out.push(Diagnostic { severity: Severity::Error, rule: self.name(), message: "stairs cross a module border".to_string(), cell: Some((s.min_x, s.min_y)) });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use crate::level::test_level;
+    use crate::tile_manifest::TileManifest;
+
+    fn rules_hit(level: &Level) -> Vec<(&'static str, Option<(usize, usize)>)> {
+        level.validate().into_iter().map(|d| (d.rule, d.cell)).collect()
+    }
+
+    #[test]
+    fn rules_come_from_the_manifest() {
+        let mut level = test_level(6, 4);
+        assert_eq!(rules_hit(&level), vec![("required-kind", None)]);
+        level.set_tile(0, 3, TileType::Custom("flagpole".into()));
+        level.set_tile(1, 1, TileType::Custom("pig".into()));
+        level.set_tile(2, 1, TileType::Custom("bird".into()));
+        level.set_tile(3, 3, TileType::Custom("powerup_tile".into()));
+        level.finish_operation();
+        assert_eq!(rules_hit(&level), vec![("required-text", Some((3, 3))), ("floating-enemy", Some((1, 1)))]);
+    }
+
+    #[test]
+    fn kinds_added_to_the_manifest_are_checked() {
+        let mut manifest = TileManifest::load("assets/tile_types.json").unwrap();
+        let mut goblin = manifest.tile_types.iter().find(|d| d.id == "pig").unwrap().clone();
+        goblin.id = "goblin".to_string();
+        goblin.required_fields = vec!["objectID".to_string()];
+        manifest.tile_types.push(goblin);
+        let mut level = Level::new(3, 3, Rc::new(TileRegistry::from_manifest_headless(&manifest)));
+        level.modules = vec![3];
+        level.set_tile(0, 2, TileType::Custom("flagpole".into()));
+        level.set_tile(1, 0, TileType::Custom("goblin".into()));
+        level.finish_operation();
+        assert_eq!(rules_hit(&level), vec![("required-text", Some((1, 0))), ("floating-enemy", Some((1, 0)))]);
+    }
+
+    #[test]
+    fn only_solid_tiles_count_as_ground() {
+        let mut level = test_level(8, 5);
+        for x in 0..8 { level.set_tile(x, 4, TileType::Custom("ground".into())); }
+        level.set_tile(7, 3, TileType::Custom("flagpole".into()));
+        for (x, below) in [(0, "wall"), (1, "snail"), (2, "oneup"), (3, "ice")] {
+            level.set_tile(x, 3, TileType::Custom(below.into()));
+            level.set_tile(x, 2, TileType::Custom("pig".into()));
+        }
+        level.place_stairs(TileType::Custom("wall".into()), 4, 2, 2, crate::tile::StairsOrientation::AscendingRight);
+        level.set_tile(4, 2, TileType::Custom("pig".into()));
+        level.finish_operation();
+        assert_eq!(rules_hit(&level), vec![("floating-enemy", Some((1, 2))), ("floating-enemy", Some((2, 2)))]);
+    }
+}
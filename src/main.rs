@@ -3,24 +3,34 @@ use egui_macroquad::macroquad;
 
 mod level;
 mod camera;
+mod cli;
 mod editor;
 mod ui;
 mod tile;
 mod tile_type_system;
-mod tile_types;
+mod tile_manifest;
 
-use editor::LevelEditor;
+use editor::{LevelEditor, NewLevelSettings};
 use ui::UI;
 
 // Removed unused imports
 
 // Constants
-const LEVEL_WIDTH: usize = 20;
 const LEVEL_HEIGHT: usize = 15;
+const TILE_MANIFEST: &str = "assets/tile_types.json";
+const MODULE_LIBRARY: &str = "module_library";
 
-#[macroquad::main("Level Editor")]
-async fn main() {
-    let mut editor = LevelEditor::new(LEVEL_WIDTH, LEVEL_HEIGHT).await;
+fn main() {
+    // Subcommands run headless; anything else opens the editor window
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if cli::is_cli_invocation(&args) {
+        std::process::exit(cli::run(&args, TILE_MANIFEST, LEVEL_HEIGHT));
+    }
+    macroquad::Window::new("Level Editor", run_editor());
+}
+
+async fn run_editor() {
+    let mut editor = LevelEditor::new(&NewLevelSettings { height: LEVEL_HEIGHT, ..Default::default() }, TILE_MANIFEST, MODULE_LIBRARY).await;
 
     egui_macroquad::cfg(|ctx| {
         ctx.style_mut(|style| {
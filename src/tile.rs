@@ -1,10 +1,12 @@
 use egui_macroquad::macroquad::prelude::*;
 use egui_macroquad::egui;
 use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
 // Removed unused import
-use crate::tile_type_system::MetaField;
+use crate::tile_type_system::{BrushType, MetaField, TileCategory, create_common_metadata};
+use crate::tile_manifest::{StructureKind, TileManifest, TileTypeDef};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Platform {
     pub tile_type: TileType,
     pub min_x: usize,
@@ -12,6 +14,9 @@ pub struct Platform {
     pub max_x: usize,
     pub max_y: usize,
     pub metadata: Vec<MetaField>,
+    // Drawn or split by hand; re-segmentation keeps the rectangle as long as its tiles still fit it
+    #[serde(default)]
+    pub locked: bool,
 }
 
 impl Platform {
@@ -19,16 +24,76 @@ impl Platform {
     pub fn height(&self) -> usize { self.max_y - self.min_y + 1 }
 }
 
-#[derive(Clone, Debug)]
+// Which side the tall column is on. The game stores this as orientation -1 (left) / 1 (right)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StairsOrientation {
+    #[default]
+    AscendingLeft,
+    AscendingRight,
+}
+
+impl StairsOrientation {
+    pub fn from_game(value: i64) -> StairsOrientation {
+        if value < 0 { StairsOrientation::AscendingLeft } else { StairsOrientation::AscendingRight }
+    }
+
+    pub fn to_game(self) -> i32 {
+        match self {
+            StairsOrientation::AscendingLeft => -1,
+            StairsOrientation::AscendingRight => 1,
+        }
+    }
+
+    pub fn flipped(self) -> StairsOrientation {
+        match self {
+            StairsOrientation::AscendingLeft => StairsOrientation::AscendingRight,
+            StairsOrientation::AscendingRight => StairsOrientation::AscendingLeft,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StairsOrientation::AscendingLeft => "Ascending left",
+            StairsOrientation::AscendingRight => "Ascending right",
+        }
+    }
+}
+
+// Stairs fill a size x size box whose top-left corner is (min_x, min_y): one column per step,
+// the tallest on the orientation's side, all standing on the bottom row
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Stairs {
     pub tile_type: TileType,
     pub min_x: usize,
     pub min_y: usize,
     pub max_x: usize,
     pub max_y: usize,
+    #[serde(default)]
+    pub size: usize,
+    #[serde(default)]
+    pub orientation: StairsOrientation,
     pub metadata: Vec<MetaField>,
 }
 
+impl Stairs {
+    pub fn new(tile_type: TileType, x: usize, y: usize, size: usize, orientation: StairsOrientation, metadata: Vec<MetaField>) -> Stairs {
+        let size = size.max(1);
+        Stairs { tile_type, min_x: x, min_y: y, max_x: x + size - 1, max_y: y + size - 1, size, orientation, metadata }
+    }
+
+    pub fn cells(&self) -> Vec<(usize, usize)> {
+        let mut cells = Vec::new();
+        for i in 0..self.size {
+            let height = match self.orientation {
+                StairsOrientation::AscendingLeft => self.size - i,
+                StairsOrientation::AscendingRight => i + 1,
+            };
+            for y in self.max_y + 1 - height..=self.max_y { cells.push((self.min_x + i, y)); }
+        }
+        cells
+    }
+}
+
 pub trait SelectableMeta {
     fn metadata_ui(&mut self, ui: &mut egui::Ui);
 }
@@ -38,7 +103,7 @@ pub trait SelectableMeta {
 
 
 // Tile type abstraction: keep Air as a special, everything else is dynamic by key
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum TileType {
     Air,
     Custom(String),
@@ -56,7 +121,7 @@ impl TileType {
 }
 
 // Complete tile with editable attributes
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Tile {
     pub tile_type: TileType,
     pub name: String,
@@ -64,18 +129,13 @@ pub struct Tile {
 }
 
 impl Tile {
-    pub fn new(tile_type: TileType) -> Self {
-        let name = match &tile_type { TileType::Air => "Air".to_string(), TileType::Custom(k) => k.clone() };
-        let metadata = default_tile_metadata_for(&tile_type);
-        Self { tile_type, name, metadata }
-    }
-
     pub fn color(&self) -> Color { WHITE }
 
-    pub fn set_tile_type(&mut self, tile_type: TileType) {
+    // Retype the tile; metadata resets to the kind's defaults from the registry
+    pub fn set_tile_type(&mut self, tile_type: TileType, registry: &TileRegistry) {
+        self.name = match &tile_type { TileType::Air => "Air".to_string(), TileType::Custom(k) => k.clone() };
+        self.metadata = registry.default_metadata(&tile_type);
         self.tile_type = tile_type;
-        self.name = match &self.tile_type { TileType::Air => "Air".to_string(), TileType::Custom(k) => k.clone() };
-        self.metadata = default_tile_metadata_for(&self.tile_type);
     }
 
     pub fn set_name(&mut self, name: String) {
@@ -105,182 +165,180 @@ impl SelectableMeta for Stairs {
         ui.label("Stairs Metadata:");
         // Computed fields: position and size (single value since square-ish by design)
         ui.label(format!("Position: ({}, {})", self.min_x, self.min_y));
-        let size = (self.max_x - self.min_x + 1).max(self.max_y - self.min_y + 1);
-        ui.label(format!("Size: {}", size));
+        ui.label(format!("Size: {}", self.size));
         for field in &mut self.metadata { field.ui(ui); }
     }
 }
 
-pub fn default_tile_metadata_for(tile_type: &TileType) -> Vec<MetaField> {
-    let mut fields: Vec<MetaField> = Vec::new();
-    // Common fields
-    fields.push(MetaField::Text { 
-        key: "objectID".to_string(), 
-        label: "Object ID".to_string(), 
-        value: String::new(),
-        editable: true,
-    });
-    let type_label = match tile_type { 
-        TileType::Air => "Air".to_string(), 
-        TileType::Custom(k) => k.clone() 
-    };
-    fields.push(MetaField::Label { 
-        label: "type".to_string(), 
-        value: type_label 
-    });
-    fields.push(MetaField::Bool { 
-        key: "enabled".to_string(), 
-        label: "Enabled".to_string(), 
-        value: true, 
-        editable: false,
-    });
-    fields.push(MetaField::Bool { 
-        key: "mutable".to_string(), 
-        label: "Mutable".to_string(), 
-        value: false, 
-        editable: true,
-    });
-
-    // Special-case: powerup tile
-    if let TileType::Custom(k) = tile_type {
-        let kl = k.to_lowercase();
-        if kl.contains("powerup") {
-            fields.push(MetaField::Text { 
-                key: "collectableClass".to_string(), 
-                label: "Collectable Class".to_string(), 
-                value: String::new(),
-                editable: true,
-            });
-        }
-    }
-    fields
-}
-
-pub fn default_platform_metadata_for(_tile_type: TileType) -> Vec<MetaField> {
-    vec![
-        MetaField::Text { 
-            key: "objectID".to_string(), 
-            label: "Object ID".to_string(), 
-            value: String::new(),
-            editable: true,
-        },
-        MetaField::Label { 
-            label: "type".to_string(), 
-            value: "Platform".to_string() 
-        },
-        MetaField::Bool { 
-            key: "enabled".to_string(), 
-            label: "Enabled".to_string(), 
-            value: true, 
-            editable: false,
-        },
-        MetaField::Bool { 
-            key: "mutable".to_string(), 
-            label: "Mutable".to_string(), 
-            value: false, 
-            editable: true,
-        },
-    ]
-}
-
-pub fn default_stairs_metadata_for(_tile_type: TileType) -> Vec<MetaField> {
-    vec![
-        MetaField::Text { 
-            key: "objectID".to_string(), 
-            label: "Object ID".to_string(), 
-            value: String::new(),
-            editable: true,
-        },
-        MetaField::Label { 
-            label: "type".to_string(), 
-            value: "Stairs".to_string() 
-        },
-        MetaField::Bool { 
-            key: "enabled".to_string(), 
-            label: "Enabled".to_string(), 
-            value: true, 
-            editable: false,
-        },
-        MetaField::Bool { 
-            key: "mutable".to_string(), 
-            label: "Mutable".to_string(), 
-            value: false, 
-            editable: true,
-        },
-    ]
-}
-
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum PlatformGroup { Grass, Ground, Wall, Ice, Mud }
+// A manifest platformGroup name, interned by the registry. Adjacent tiles of the same group merge into platforms
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PlatformGroup(usize);
 
+// One tile kind as declared in the manifest. Plain kinds are painted cell by cell; structure
+// kinds (platform, stairs) select a structure brush instead
 #[derive(Clone)]
 pub struct TileKind {
     pub key: String,
     pub display_name: String,
+    pub export_name: String,
+    pub category: TileCategory,
     pub texture: Option<Texture2D>,
+    pub color: Option<[u8; 3]>, // Manifest colour, used where textures aren't drawn (e.g. thumbnails)
     pub platform_group: Option<PlatformGroup>,
+    pub structure: Option<StructureKind>,
+    pub brushes: Vec<BrushType>,
+    pub metadata: Vec<MetaField>, // Defaults for newly placed objects of this kind
+    pub required_in_level: bool,
+    pub needs_ground: bool,
+    pub required_fields: Vec<String>,
+}
+
+impl TileKind {
+    pub fn can_use_brush(&self, brush_type: BrushType) -> bool { self.brushes.contains(&brush_type) }
 }
 
+// The single source of tile kinds: drawing, platform grouping, default metadata, the tile
+// selector, the inspector and json import/export all look kinds up here
 pub struct TileRegistry {
     kinds: Vec<TileKind>,
     name_to_index: HashMap<String, usize>,
+    group_names: Vec<String>, // Indexed by PlatformGroup
+}
+
+impl Default for TileRegistry {
+    fn default() -> Self { TileRegistry::with_air() }
 }
 
 impl TileRegistry {
-    pub async fn load_from_dir(dir: &str) -> TileRegistry {
+    pub async fn from_manifest(manifest: &TileManifest) -> TileRegistry {
+        let mut registry = TileRegistry::with_air();
+        for def in &manifest.tile_types {
+            let texture = load_def_texture(manifest, def).await;
+            registry.push_kind(def, Some(texture));
+        }
+        registry
+    }
+
+    // Same kinds as from_manifest but without textures, so it works without a graphics context
+    pub fn from_manifest_headless(manifest: &TileManifest) -> TileRegistry {
+        let mut registry = TileRegistry::with_air();
+        for def in &manifest.tile_types {
+            registry.push_kind(def, None);
+        }
+        registry
+    }
+
+    fn with_air() -> TileRegistry {
         let mut kinds: Vec<TileKind> = Vec::new();
         let mut name_to_index: HashMap<String, usize> = HashMap::new();
 
         // Always include Air as index 0
-        kinds.push(TileKind { key: "air".into(), display_name: "Air".into(), texture: None, platform_group: None });
+        kinds.push(TileKind {
+            key: "air".into(), display_name: "Air".into(), export_name: "Air".into(), category: TileCategory::Tiles,
+            texture: None, color: None, platform_group: None, structure: None, brushes: vec![BrushType::Drawing, BrushType::Selector], metadata: Vec::new(),
+            required_in_level: false, needs_ground: false, required_fields: Vec::new(),
+        });
         name_to_index.insert("air".into(), 0);
 
-        if let Ok(entries) = std::fs::read_dir(dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.is_file() {
-                    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-                        let ext = ext.to_lowercase();
-                        if ext == "png" || ext == "jpg" || ext == "jpeg" {
-                            let key = path.file_stem().and_then(|s| s.to_str()).unwrap_or("").to_string();
-                            let display_name = key.replace('_', " ");
-                            let tex_path = path.to_string_lossy().to_string();
-                            let texture = load_texture(&tex_path).await.ok();
-                            let platform_group = infer_platform_group_from_key(&key);
-                            let idx = kinds.len();
-                            kinds.push(TileKind { key: key.clone(), display_name, texture, platform_group });
-                            name_to_index.insert(key, idx);
-                        }
-                    }
-                }
+        TileRegistry { kinds, name_to_index, group_names: Vec::new() }
+    }
+
+    fn push_kind(&mut self, def: &TileTypeDef, texture: Option<Texture2D>) {
+        let platform_group = def.platform_group.as_deref().map(|name| self.intern_group(name));
+        let idx = self.kinds.len();
+        self.kinds.push(TileKind {
+            key: def.id.clone(),
+            display_name: def.display_name.clone(),
+            export_name: def.export_name(),
+            category: def.category,
+            texture,
+            color: def.color,
+            platform_group,
+            structure: def.structure,
+            brushes: def.brushes.clone(),
+            metadata: def.default_metadata(),
+            required_in_level: def.required_in_level,
+            needs_ground: def.needs_ground,
+            required_fields: def.required_fields.clone(),
+        });
+        self.name_to_index.insert(def.id.clone(), idx);
+    }
+
+    fn intern_group(&mut self, name: &str) -> PlatformGroup {
+        match self.group_names.iter().position(|g| g == name) {
+            Some(i) => PlatformGroup(i),
+            None => {
+                self.group_names.push(name.to_string());
+                PlatformGroup(self.group_names.len() - 1)
             }
         }
-
-        TileRegistry { kinds, name_to_index }
     }
 
     pub fn kinds(&self) -> &[TileKind] { &self.kinds }
     pub fn get(&self, key: &str) -> Option<&TileKind> { self.name_to_index.get(key).and_then(|&i| self.kinds.get(i)) }
+    pub fn kind_of(&self, tile_type: &TileType) -> Option<&TileKind> {
+        match tile_type { TileType::Air => None, TileType::Custom(k) => self.get(k) }
+    }
+    // Kinds of one category in manifest order, air excluded
+    pub fn by_category(&self, category: TileCategory) -> Vec<&TileKind> {
+        self.kinds.iter().skip(1).filter(|k| k.category == category).collect()
+    }
     pub fn texture_for(&self, tile_type: &TileType) -> Option<&Texture2D> {
-        match tile_type { TileType::Air => None, TileType::Custom(k) => self.get(k).and_then(|t| t.texture.as_ref()) }
+        self.kind_of(tile_type).and_then(|t| t.texture.as_ref())
+    }
+    pub fn color_for(&self, tile_type: &TileType) -> Option<[u8; 3]> {
+        self.kind_of(tile_type).and_then(|t| t.color)
     }
     pub fn platform_group_for(&self, tile_type: &TileType) -> Option<PlatformGroup> {
-        match tile_type { TileType::Air => None, TileType::Custom(k) => self.get(k).and_then(|t| t.platform_group) }
+        self.kind_of(tile_type).and_then(|t| t.platform_group)
+    }
+    pub fn structure_for(&self, tile_type: &TileType) -> Option<StructureKind> {
+        self.kind_of(tile_type).and_then(|t| t.structure)
+    }
+    pub fn display_name_for(&self, tile_type: &TileType) -> String {
+        match tile_type {
+            TileType::Air => "Air".to_string(),
+            TileType::Custom(k) => self.get(k).map_or_else(|| k.clone(), |t| t.display_name.clone()),
+        }
+    }
+
+    // Kinds missing from the manifest (e.g. kept from an import) still get the common fields
+    pub fn default_metadata(&self, tile_type: &TileType) -> Vec<MetaField> {
+        match tile_type {
+            TileType::Air => Vec::new(),
+            TileType::Custom(k) => self.get(k).map_or_else(|| common_metadata_with_type(k), |t| t.metadata.clone()),
+        }
     }
+
+    pub fn structure_metadata(&self, structure: StructureKind) -> Vec<MetaField> {
+        match self.kinds.iter().find(|k| k.structure == Some(structure)) {
+            Some(kind) => kind.metadata.clone(),
+            None => common_metadata_with_type(match structure { StructureKind::Platform => "Platform", StructureKind::Stairs => "Stairs" }),
+        }
+    }
+}
+
+fn common_metadata_with_type(type_name: &str) -> Vec<MetaField> {
+    let mut fields = create_common_metadata();
+    if let Some(MetaField::Label { value, .. }) = fields.iter_mut().find(|f| matches!(f, MetaField::Label { label, .. } if label == "type")) {
+        *value = type_name.to_string();
+    }
+    fields
 }
 
-fn infer_platform_group_from_key(key: &str) -> Option<PlatformGroup> {
-    let k = key.to_lowercase();
-    if k.contains("grass") { Some(PlatformGroup::Grass) }
-    else if k.contains("ground") { Some(PlatformGroup::Ground) }
-    else if k.contains("wall") { Some(PlatformGroup::Wall) }
-    else if k.contains("ice") { Some(PlatformGroup::Ice) }
-    else if k.contains("mud") { Some(PlatformGroup::Mud) }
-    else { None }
+// Texture from the manifest, or a flat colour square if it is missing or fails to load
+async fn load_def_texture(manifest: &TileManifest, def: &TileTypeDef) -> Texture2D {
+    if let Some(path) = manifest.texture_path(def)
+        && let Ok(texture) = load_texture(&path.to_string_lossy()).await {
+        return texture;
+    }
+    let color = def.color.map(|[r, g, b]| Color::from_rgba(r, g, b, 255)).unwrap_or(WHITE);
+    let image = Image::gen_image_color(32, 32, color);
+    Texture2D::from_image(&image)
 }
 
 impl Default for Tile {
     fn default() -> Self {
-        Self::new(TileType::Air)
+        Self { tile_type: TileType::Air, name: "Air".to_string(), metadata: Vec::new() }
     }
 } 
\ No newline at end of file
@@ -0,0 +1,128 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use serde::Deserialize;
+
+use crate::tile_type_system::{BrushType, MetaField, TileCategory, create_common_metadata};
+
+// Every tile kind the editor knows about, read from a json file so new kinds need no rebuild.
+// Texture paths are relative to the manifest's own directory.
+
+// Multi-tile structures get special drawing behaviour; everything else is a plain tile kind
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum StructureKind {
+    Platform,
+    Stairs,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TileTypeDef {
+    pub id: String,
+    pub display_name: String,
+    pub category: TileCategory,
+    #[serde(default)]
+    pub texture: Option<String>,
+    // Fallback colour (rgb 0-255) when the texture is missing
+    #[serde(default)]
+    pub color: Option<[u8; 3]>,
+    // Name written to the game json; defaults to the id with underscores as spaces
+    #[serde(default)]
+    pub export_name: Option<String>,
+    // Kinds naming the same group merge into platforms with each other; any name may be used
+    #[serde(default)]
+    pub platform_group: Option<String>,
+    #[serde(default)]
+    pub structure: Option<StructureKind>,
+    #[serde(default = "all_brushes")]
+    pub brushes: Vec<BrushType>,
+    // Prepend objectID/type/enabled/mutable to the declared fields
+    #[serde(default = "default_true")]
+    pub common_metadata: bool,
+    // Validation: every level needs one of this kind / it must stand on something / these text
+    // fields must not be left empty
+    #[serde(default)]
+    pub required_in_level: bool,
+    #[serde(default)]
+    pub needs_ground: bool,
+    #[serde(default)]
+    pub required_fields: Vec<String>,
+    #[serde(default)]
+    pub metadata: Vec<MetaField>,
+}
+
+fn all_brushes() -> Vec<BrushType> { vec![BrushType::Drawing, BrushType::Selector] }
+fn default_true() -> bool { true }
+
+impl TileTypeDef {
+    pub fn export_name(&self) -> String {
+        self.export_name.clone().unwrap_or_else(|| self.id.replace('_', " "))
+    }
+
+    // Metadata a freshly placed object of this kind starts with
+    pub fn default_metadata(&self) -> Vec<MetaField> {
+        let mut fields = if self.common_metadata { create_common_metadata() } else { Vec::new() };
+        if let Some(MetaField::Label { value, .. }) = fields.iter_mut().find(|f| matches!(f, MetaField::Label { label, .. } if label == "type")) {
+            *value = self.display_name.clone();
+        }
+        fields.extend(self.metadata.iter().cloned());
+        fields
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TileManifest {
+    pub tile_types: Vec<TileTypeDef>,
+    #[serde(skip)]
+    base_dir: PathBuf,
+}
+
+#[derive(Debug)]
+pub enum ManifestError {
+    Io(String, std::io::Error),
+    Parse(String, serde_json::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManifestError::Io(path, e) => write!(f, "{}: {}", path, e),
+            ManifestError::Parse(path, e) => write!(f, "{}: {}", path, e),
+            ManifestError::Invalid(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl TileManifest {
+    pub fn load(path: &str) -> Result<TileManifest, ManifestError> {
+        let contents = std::fs::read_to_string(path).map_err(|e| ManifestError::Io(path.to_string(), e))?;
+        let mut manifest: TileManifest = serde_json::from_str(&contents).map_err(|e| ManifestError::Parse(path.to_string(), e))?;
+        manifest.base_dir = Path::new(path).parent().map(Path::to_path_buf).unwrap_or_default();
+        manifest.check()?;
+        Ok(manifest)
+    }
+
+    fn check(&self) -> Result<(), ManifestError> {
+        let mut seen = std::collections::HashSet::new();
+        for def in &self.tile_types {
+            if def.id.is_empty() || def.id == "air" {
+                return Err(ManifestError::Invalid(format!("invalid tile type id {:?}", def.id)));
+            }
+            if !seen.insert(def.id.as_str()) {
+                return Err(ManifestError::Invalid(format!("tile type {:?} is declared twice", def.id)));
+            }
+            if def.platform_group.as_ref().is_some_and(|g| g.is_empty()) {
+                return Err(ManifestError::Invalid(format!("tile type {:?} has an empty platform group", def.id)));
+            }
+            if def.structure.is_some() && def.platform_group.is_some() {
+                return Err(ManifestError::Invalid(format!("structure {:?} cannot have a platform group", def.id)));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn texture_path(&self, def: &TileTypeDef) -> Option<PathBuf> {
+        def.texture.as_ref().map(|t| self.base_dir.join(t))
+    }
+}
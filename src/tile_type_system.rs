@@ -1,6 +1,4 @@
-use egui_macroquad::macroquad::prelude::*;
 use egui_macroquad::egui;
-use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
 
 // Categories for organizing tile types
@@ -24,7 +22,7 @@ impl TileCategory {
 }
 
 // Brush types that can be used with tile types
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BrushType {
     Drawing,
     Selector,
@@ -40,7 +38,7 @@ impl BrushType {
 }
 
 // Metadata field types
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum MetaField {
     Number { 
         key: String, 
@@ -76,7 +74,9 @@ pub enum MetaField {
 }
 
 impl MetaField {
-    pub fn ui(&mut self, ui: &mut egui::Ui) {
+    // Draw the field; returns true if the user changed its value this frame
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
         match self {
             MetaField::Number { label, value, min, max, editable, .. } => {
                 ui.horizontal(|ui| {
@@ -84,6 +84,7 @@ impl MetaField {
                     let mut v = *value;
                     if ui.add_enabled(*editable, egui::Slider::new(&mut v, *min..=*max)).changed() { 
                         *value = v; 
+                        changed = true;
                     }
                 });
             }
@@ -93,6 +94,7 @@ impl MetaField {
                     let mut buf = value.clone();
                     if ui.add_enabled(*editable, egui::TextEdit::singleline(&mut buf)).changed() { 
                         *value = buf; 
+                        changed = true;
                     }
                 });
             }
@@ -100,7 +102,7 @@ impl MetaField {
                 ui.horizontal(|ui| {
                     ui.label(label.clone());
                     let mut b = *value;
-                    ui.add_enabled(*editable, egui::Checkbox::new(&mut b, ""));
+                    changed = ui.add_enabled(*editable, egui::Checkbox::new(&mut b, "")).changed();
                     *value = b;
                 });
             }
@@ -119,12 +121,14 @@ impl MetaField {
                             for (i, opt) in options.iter().enumerate() {
                                 if ui.selectable_label(*selected == i, opt.clone()).clicked() { 
                                     *selected = i; 
+                                    changed = true;
                                 }
                             }
                         });
                 });
             }
         }
+        changed
     }
     
     pub fn key(&self) -> &str {
@@ -137,6 +141,26 @@ impl MetaField {
         }
     }
     
+    // Same field on two objects: same variant, key and (for choices) options
+    pub fn same_field(&self, other: &MetaField) -> bool {
+        match (self, other) {
+            (MetaField::Choice { key: a, options: oa, .. }, MetaField::Choice { key: b, options: ob, .. }) => a == b && oa == ob,
+            (MetaField::Label { .. }, _) | (_, MetaField::Label { .. }) => false,
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other) && self.key() == other.key(),
+        }
+    }
+
+    // Take over the value of a matching field, keeping this field's own range and flags
+    pub fn set_value_from(&mut self, other: &MetaField) {
+        match (self, other) {
+            (MetaField::Number { value, min, max, .. }, MetaField::Number { value: v, .. }) => *value = v.clamp(*min, *max),
+            (MetaField::Text { value, .. }, MetaField::Text { value: v, .. }) => *value = v.clone(),
+            (MetaField::Bool { value, .. }, MetaField::Bool { value: v, .. }) => *value = *v,
+            (MetaField::Choice { selected, .. }, MetaField::Choice { selected: s, .. }) => *selected = *s,
+            _ => {}
+        }
+    }
+
     pub fn is_editable(&self) -> bool {
         match self {
             MetaField::Number { editable, .. } => *editable,
@@ -175,210 +199,3 @@ pub fn create_common_metadata() -> Vec<MetaField> {
         },
     ]
 }
-
-// Enum-based tile type system
-#[derive(Clone, Debug)]
-pub enum TileType {
-    BasicTile {
-        id: String,
-        display_name: String,
-        category: TileCategory,
-        texture: Texture2D,
-        metadata: Vec<MetaField>,
-        position: Option<(u32, u32)>,
-    },
-    PlatformTile {
-        id: String,
-        display_name: String,
-        min_x: usize,
-        min_y: usize,
-        max_x: usize,
-        max_y: usize,
-        metadata: Vec<MetaField>,
-        texture: Texture2D,
-    },
-    StairsTile {
-        id: String,
-        display_name: String,
-        min_x: usize,
-        min_y: usize,
-        max_x: usize,
-        max_y: usize,
-        orientation: i32,
-        metadata: Vec<MetaField>,
-        texture: Texture2D,
-    },
-}
-
-impl TileType {
-    pub fn id(&self) -> &str {
-        match self {
-            TileType::BasicTile { id, .. } => id,
-            TileType::PlatformTile { id, .. } => id,
-            TileType::StairsTile { id, .. } => id,
-        }
-    }
-    
-    pub fn display_name(&self) -> &str {
-        match self {
-            TileType::BasicTile { display_name, .. } => display_name,
-            TileType::PlatformTile { display_name, .. } => display_name,
-            TileType::StairsTile { display_name, .. } => display_name,
-        }
-    }
-    
-    pub fn category(&self) -> TileCategory {
-        match self {
-            TileType::BasicTile { category, .. } => *category,
-            TileType::PlatformTile { .. } => TileCategory::Structures,
-            TileType::StairsTile { .. } => TileCategory::Structures,
-        }
-    }
-    
-    pub fn texture(&self) -> &Texture2D {
-        match self {
-            TileType::BasicTile { texture, .. } => texture,
-            TileType::PlatformTile { texture, .. } => texture,
-            TileType::StairsTile { texture, .. } => texture,
-        }
-    }
-    
-    
-    pub fn position(&self) -> Option<(u32, u32)> {
-        match self {
-            TileType::BasicTile { position, .. } => *position,
-            TileType::PlatformTile { min_x, min_y, .. } => Some((*min_x as u32, *min_y as u32)),
-            TileType::StairsTile { min_x, min_y, .. } => Some((*min_x as u32, *min_y as u32)),
-        }
-    }
-    
-    pub fn set_position(&mut self, pos: (u32, u32)) {
-        match self {
-            TileType::BasicTile { position, .. } => *position = Some(pos),
-            TileType::PlatformTile { min_x, min_y, max_x, max_y, .. } => {
-                let width = *max_x - *min_x + 1;
-                let height = *max_y - *min_y + 1;
-                *min_x = pos.0 as usize;
-                *min_y = pos.1 as usize;
-                *max_x = *min_x + width - 1;
-                *max_y = *min_y + height - 1;
-            },
-            TileType::StairsTile { min_x, min_y, max_x, max_y, .. } => {
-                let width = *max_x - *min_x + 1;
-                let height = *max_y - *min_y + 1;
-                *min_x = pos.0 as usize;
-                *min_y = pos.1 as usize;
-                *max_x = *min_x + width - 1;
-                *max_y = *min_y + height - 1;
-            },
-        }
-    }
-    
-    pub fn size(&self) -> Option<(u32, u32)> {
-        match self {
-            TileType::BasicTile { .. } => None,
-            TileType::PlatformTile { min_x, min_y, max_x, max_y, .. } => {
-                Some(((max_x - min_x + 1) as u32, (max_y - min_y + 1) as u32))
-            },
-            TileType::StairsTile { min_x, min_y, max_x, max_y, .. } => {
-                let size = (max_x - min_x + 1).max(max_y - min_y + 1);
-                Some((size as u32, size as u32))
-            },
-        }
-    }
-    
-    pub fn can_use_brush(&self, brush_type: BrushType) -> bool {
-        match self.category() {
-            TileCategory::Tiles => matches!(brush_type, BrushType::Drawing | BrushType::Selector),
-            TileCategory::Enemies => matches!(brush_type, BrushType::Drawing | BrushType::Selector),
-            TileCategory::Collectables => matches!(brush_type, BrushType::Drawing | BrushType::Selector),
-            TileCategory::Structures => matches!(brush_type, BrushType::Drawing | BrushType::Selector),
-        }
-    }
-    
-    pub fn metadata(&self) -> &Vec<MetaField> {
-        match self {
-            TileType::BasicTile { metadata, .. } => metadata,
-            TileType::PlatformTile { metadata, .. } => metadata,
-            TileType::StairsTile { metadata, .. } => metadata,
-        }
-    }
-    
-    pub fn metadata_mut(&mut self) -> &mut Vec<MetaField> {
-        match self {
-            TileType::BasicTile { metadata, .. } => metadata,
-            TileType::PlatformTile { metadata, .. } => metadata,
-            TileType::StairsTile { metadata, .. } => metadata,
-        }
-    }
-    
-    pub fn editable_metadata(&self) -> Vec<MetaField> {
-        self.metadata().iter().filter(|f| f.is_editable()).cloned().collect()
-    }
-    
-    pub fn render_metadata_ui(&mut self, ui: &mut egui::Ui) {
-        // Default implementation: show type as title and render all metadata fields
-        ui.label(format!("{} Metadata:", self.display_name()));
-        
-        // Show position if available
-        if let Some(pos) = self.position() {
-            ui.label(format!("Position: ({}, {})", pos.0, pos.1));
-        }
-        
-        // Show size if available
-        if let Some(size) = self.size() {
-            ui.label(format!("Size: {} x {}", size.0, size.1));
-        }
-        
-        // Render all metadata fields
-        for field in self.metadata_mut() {
-            field.ui(ui);
-        }
-    }
-}
-
-// Registry for managing all tile types
-pub struct TileTypeRegistry {
-    tile_types: HashMap<String, TileType>,
-    category_index: HashMap<TileCategory, Vec<String>>,
-}
-
-impl TileTypeRegistry {
-    pub fn new() -> Self {
-        Self {
-            tile_types: HashMap::new(),
-            category_index: HashMap::new(),
-        }
-    }
-    
-    pub fn register(&mut self, tile_type: TileType) {
-        let id = tile_type.id().to_string();
-        let category = tile_type.category();
-        
-        self.tile_types.insert(id.clone(), tile_type);
-        self.category_index.entry(category).or_insert_with(Vec::new).push(id);
-    }
-    
-    pub fn get(&self, id: &str) -> Option<&TileType> {
-        self.tile_types.get(id)
-    }
-    
-    pub fn get_mut(&mut self, id: &str) -> Option<&mut TileType> {
-        self.tile_types.get_mut(id)
-    }
-    
-    pub fn get_by_category(&self, category: TileCategory) -> Vec<&TileType> {
-        self.category_index
-            .get(&category)
-            .map(|ids| {
-                ids.iter()
-                    .filter_map(|id| self.tile_types.get(id))
-                    .collect()
-            })
-            .unwrap_or_default()
-    }
-    
-    pub fn all_tile_types(&self) -> Vec<&TileType> {
-        self.tile_types.values().collect()
-    }
-}
@@ -0,0 +1,80 @@
+use egui_macroquad::egui::{self, Context, Color32};
+use crate::editor::LevelEditor;
+use crate::level::{ImportError, IssueKind};
+
+// Window listing what happened during the last import; stays open until dismissed
+pub fn show_import_report(egui_ctx: &Context, editor: &mut LevelEditor) {
+    let Some(outcome) = editor.import_outcome() else { return; };
+
+    let mut open = true;
+    egui::Window::new("Import Report")
+        .open(&mut open)
+        .default_width(420.0)
+        .show(egui_ctx, |ui| {
+            let report = match outcome {
+                Ok(report) => {
+                    if report.is_clean() {
+                        ui.label("Level imported without issues.");
+                    } else {
+                        ui.label(format!("Level imported with {} issue(s):", report.issues.len()));
+                    }
+                    Some(report)
+                }
+                Err(ImportError::Rejected(report)) => {
+                    ui.colored_label(Color32::RED, "Strict import rejected the file, the level was not changed.");
+                    Some(report)
+                }
+                Err(e) => {
+                    ui.colored_label(Color32::RED, e.to_string());
+                    None
+                }
+            };
+
+            if let Some(report) = report.filter(|r| !r.is_clean()) {
+                ui.separator();
+                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    egui::Grid::new("import_issues").striped(true).show(ui, |ui| {
+                        ui.strong("Module");
+                        ui.strong("Object");
+                        ui.strong("Issue");
+                        ui.strong("Reason");
+                        ui.end_row();
+                        for issue in &report.issues {
+                            let color = match issue.kind {
+                                IssueKind::Skipped => Color32::RED,
+                                IssueKind::UnknownKind | IssueKind::Clamped => Color32::from_rgb(200, 120, 0),
+                                IssueKind::Defaulted => ui.visuals().text_color(),
+                            };
+                            ui.label(issue.module_index.to_string());
+                            ui.label(issue.object_index.map(|i| i.to_string()).unwrap_or_else(|| "-".to_string()));
+                            ui.colored_label(color, issue.kind.name());
+                            ui.label(&issue.reason);
+                            ui.end_row();
+                        }
+                    });
+                });
+            }
+        });
+
+    if !open {
+        editor.dismiss_import_outcome();
+    }
+}
+
+// A project that couldn't be saved or opened; the level and its unsaved-changes mark are untouched
+pub fn show_project_error(egui_ctx: &Context, editor: &mut LevelEditor) {
+    let Some(error) = editor.project_error() else { return; };
+
+    let mut open = true;
+    let mut dismissed = false;
+    egui::Window::new("Project Error")
+        .open(&mut open)
+        .collapsible(false)
+        .show(egui_ctx, |ui| {
+            ui.colored_label(Color32::RED, error);
+            if ui.button("OK").clicked() { dismissed = true; }
+        });
+    if !open || dismissed {
+        editor.dismiss_project_error();
+    }
+}
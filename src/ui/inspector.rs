@@ -1,73 +1,195 @@
-use egui_macroquad::egui::{self, Context};
-use crate::editor::LevelEditor;
-
-pub fn show_inspector(egui_ctx: &Context, editor: &mut LevelEditor) {
-    let coords = editor.get_selected_tile_coords();
-    egui::SidePanel::right("tile_inspector_panel")
-        .resizable(true)
-        .default_width(250.0)
-        .show(egui_ctx, |ui| {
-            ui.heading("Tile Inspector");
-            ui.separator();
-            
-            // Tile position
-            if let Some((x, y)) = coords {
-                ui.label(format!("Position: ({}, {})", x, y));
-            }
-
-            // Platform info if present
-            if let Some((_ptype, min_x, min_y, max_x, max_y)) = editor.get_selected_platform_info() {
-                ui.separator();
-                ui.label("Platform:");
-                // Type display removed; dynamic types have no static name
-                ui.label(format!("Bounds: ({}, {}) - ({}, {})", min_x, min_y, max_x, max_y));
-                ui.label(format!("Size: {} x {}", max_x - min_x + 1, max_y - min_y + 1));
-            }
-            
-            
-            ui.separator();
-            
-            // Metadata UI: show structure metadata (platform or stairs) if present, else tile metadata
-            if let Some((x, y)) = coords {
-                // Check if this is a stairs first, then platform, then regular tile
-                if let Some(stairs) = editor.level().stairs_at(x, y) {
-                    // This is a stairs - show stairs metadata directly
-                    ui.label("Type: Stairs");
-                    ui.label(format!("Size: {} x {}", stairs.max_x - stairs.min_x + 1, stairs.max_y - stairs.min_y + 1));
-                    // Edit stairs metadata directly
-                    if let Some(stairs_mut) = editor.level_mut().stairs_at_mut(x, y) {
-                        for field in &mut stairs_mut.metadata {
-                            field.ui(ui);
-                        }
-                    }
-                } else if let Some(_platform) = editor.level().platform_at(x, y) {
-                    // This is a platform - show platform metadata directly
-                    ui.label("Type: Platform");
-                    // Edit platform metadata directly
-                    if let Some(platform_mut) = editor.level_mut().platform_at_mut(x, y) {
-                        for field in &mut platform_mut.metadata {
-                            field.ui(ui);
-                        }
-                    }
-                } else if let Some(tile) = editor.level().get_tile(x, y) {
-                    // This is a regular tile
-                    let tile_type_string = tile.tile_type.to_string();
-                    if tile_type_string != "air" { // Skip metadata for air tiles
-                        if let Some(tile_type) = editor.tile_type_registry().get(&tile_type_string) {
-                            ui.label(format!("Type: {}", tile_type.display_name()));
-                            // Edit tile metadata directly
-                            if let Some(tile_mut) = editor.level_mut().get_tile_mut(x, y) {
-                                for field in &mut tile_mut.metadata {
-                                    field.ui(ui);
-                                }
-                            }
-                        }
-                    } else {
-                        ui.label("Type: Air (no metadata)");
-                    }
-                }
-            }
-            
-            ui.separator();
-        });
-}
\ No newline at end of file
+use egui_macroquad::egui::{self, Context};
+use crate::editor::LevelEditor;
+use crate::level::{ObjectRef, set_id};
+use crate::tile::TileType;
+use crate::tile_type_system::{MetaField, TileCategory};
+
+pub fn show_inspector(egui_ctx: &Context, editor: &mut LevelEditor) {
+    let coords = editor.get_selected_tile_coords();
+    let selected_objects = editor.selection().map(|rect| editor.level().objects_in_rect(rect)).unwrap_or_default();
+    egui::SidePanel::right("tile_inspector_panel")
+        .resizable(true)
+        .default_width(250.0)
+        .show(egui_ctx, |ui| {
+            ui.heading("Tile Inspector");
+            ui.separator();
+            
+            // A selection covering several objects edits their shared fields together
+            if selected_objects.len() > 1 {
+                if let Some(rect) = editor.selection().filter(|&r| editor.level().mergeable_platforms(r).is_some()) {
+                    if ui.button("Merge Platforms").clicked() {
+                        editor.level_mut().merge_platforms(rect);
+                    }
+                    ui.separator();
+                }
+                bulk_metadata_editor(ui, editor, &selected_objects);
+                return;
+            }
+
+            // Tile position
+            if let Some((x, y)) = coords {
+                ui.label(format!("Position: ({}, {})", x, y));
+            }
+
+            // Platform info if present
+            if let Some((_ptype, min_x, min_y, max_x, max_y)) = editor.get_selected_platform_info() {
+                ui.separator();
+                ui.label("Platform:");
+                // Type display removed; dynamic types have no static name
+                ui.label(format!("Bounds: ({}, {}) - ({}, {})", min_x, min_y, max_x, max_y));
+                ui.label(format!("Size: {} x {}", max_x - min_x + 1, max_y - min_y + 1));
+            }
+            
+            
+            ui.separator();
+            
+            // Metadata UI: show structure metadata (platform or stairs) if present, else tile metadata
+            if let Some((x, y)) = coords {
+                // Check if this is a stairs first, then platform, then regular tile
+                match editor.level().object_at(x, y) {
+                    Some(ObjectRef::Stairs(idx)) => {
+                        ui.label("Type: Stairs");
+                        stairs_shape(ui, editor, idx);
+                        metadata_editor(ui, editor, ObjectRef::Stairs(idx));
+                    }
+                    Some(ObjectRef::Platform(idx)) => {
+                        ui.label("Type: Platform");
+                        platform_segmentation(ui, editor, idx, x, y);
+                        metadata_editor(ui, editor, ObjectRef::Platform(idx));
+                    }
+                    Some(ObjectRef::Tile(x, y)) => {
+                        // This is a regular tile
+                        let tile_type = &editor.level().tiles[y][x].tile_type;
+                        if *tile_type != TileType::Air { // Skip metadata for air tiles
+                            ui.label(format!("Type: {}", editor.registry().display_name_for(tile_type)));
+                            metadata_editor(ui, editor, ObjectRef::Tile(x, y));
+                            save_preset_row(ui, editor, x, y);
+                        } else {
+                            ui.label("Type: Air (no metadata)");
+                        }
+                    }
+                    None => {}
+                }
+            }
+            
+            ui.separator();
+        });
+}
+
+// Edit a copy of the object's metadata and write it back only when something changed,
+// so each edit becomes one undoable step
+fn metadata_editor(ui: &mut egui::Ui, editor: &mut LevelEditor, object: ObjectRef) {
+    let Some(mut fields) = editor.level().metadata_of(object).cloned() else { return; };
+    let mut changed = false;
+    for field in &mut fields {
+        changed |= field.ui(ui);
+    }
+    if changed {
+        editor.level_mut().set_metadata(object, fields);
+    }
+    object_id_row(ui, editor, object);
+}
+
+// Flag an id another object already uses, and offer a fresh one
+fn object_id_row(ui: &mut egui::Ui, editor: &mut LevelEditor, object: ObjectRef) {
+    let Some(id) = editor.level().object_id(object).map(str::to_string) else { return };
+    if editor.level().id_in_use(&id, Some(object)) {
+        ui.colored_label(egui::Color32::from_rgb(230, 80, 80), format!("objectID {:?} is used by another object", id));
+    }
+    if ui.small_button("Generate ID").clicked() {
+        let new_id = editor.level_mut().next_object_id(object);
+        let Some(mut fields) = editor.level().metadata_of(object).cloned() else { return };
+        set_id(&mut fields, new_id);
+        editor.level_mut().set_metadata(object, fields);
+    }
+}
+
+// Lock state and hand splitting; the split runs along the left or top edge of the selected cell
+fn platform_segmentation(ui: &mut egui::Ui, editor: &mut LevelEditor, index: usize, x: usize, y: usize) {
+    let Some(platform) = editor.level().platforms().get(index).cloned() else { return };
+    let mut locked = platform.locked;
+    if ui.checkbox(&mut locked, "Locked").on_hover_text("Keep this rectangle when platforms are re-segmented").changed() {
+        editor.level_mut().set_platform_locked(index, locked);
+    }
+    ui.horizontal(|ui| {
+        if ui.add_enabled(x > platform.min_x, egui::Button::new("Split at column")).clicked() {
+            editor.level_mut().split_platform(index, x, y, true);
+        }
+        if ui.add_enabled(y > platform.min_y, egui::Button::new("Split at row")).clicked() {
+            editor.level_mut().split_platform(index, x, y, false);
+        }
+    });
+}
+
+// Size, orientation and tile type of stairs. Changes that don't fit (level edge, module border,
+// other stairs) are ignored
+fn stairs_shape(ui: &mut egui::Ui, editor: &mut LevelEditor, index: usize) {
+    let Some(stairs) = editor.level().stairs.get(index).cloned() else { return };
+    let (mut size, mut orientation, mut tile_type) = (stairs.size, stairs.orientation, stairs.tile_type.clone());
+    ui.horizontal(|ui| {
+        ui.label("Size:");
+        ui.add(egui::DragValue::new(&mut size).range(1..=stairs.max_y + 1));
+    });
+    ui.horizontal(|ui| {
+        ui.label(orientation.name());
+        if ui.button("Flip").clicked() { orientation = orientation.flipped(); }
+    });
+    let registry = editor.level().registry();
+    egui::ComboBox::from_label("Tile")
+        .selected_text(registry.display_name_for(&tile_type))
+        .show_ui(ui, |ui| {
+            for kind in registry.by_category(TileCategory::Tiles).into_iter().filter(|k| k.structure.is_none()) {
+                ui.selectable_value(&mut tile_type, TileType::Custom(kind.key.clone()), &kind.display_name);
+            }
+        });
+    if (size, orientation, &tile_type) != (stairs.size, stairs.orientation, &stairs.tile_type) {
+        editor.level_mut().set_stairs_shape(index, size, orientation, tile_type);
+    }
+}
+
+// Shared fields of several objects. Differing values are shown as mixed; changing a field
+// writes it to every object in one undoable step
+fn bulk_metadata_editor(ui: &mut egui::Ui, editor: &mut LevelEditor, objects: &[ObjectRef]) {
+    ui.label(format!("{} objects selected", objects.len()));
+    if ui.small_button("Generate IDs").on_hover_text("Give each selected object a fresh objectID").clicked() {
+        editor.level_mut().generate_ids(objects);
+    }
+    ui.separator();
+    let common = editor.level().common_metadata(objects);
+    if common.is_empty() {
+        ui.label("No shared metadata fields");
+        return;
+    }
+    for (mut field, mixed) in common {
+        let changed = match &mut field {
+            MetaField::Bool { label, value, .. } if mixed => ui.horizontal(|ui| {
+                ui.label(label.clone());
+                ui.add(egui::Checkbox::new(value, "").indeterminate(true)).changed()
+            }).inner,
+            _ => ui.horizontal(|ui| {
+                // Mixed text starts empty so a typed value replaces all of them
+                if let (true, MetaField::Text { value, .. }) = (mixed, &mut field) { value.clear(); }
+                let changed = field.ui(ui);
+                if mixed { ui.weak("(mixed)"); }
+                changed
+            }).inner,
+        };
+        if changed {
+            editor.level_mut().set_metadata_field(objects, &field);
+        }
+    }
+}
+
+// Name field plus button that stores the tile's current values as a preset of its kind
+fn save_preset_row(ui: &mut egui::Ui, editor: &mut LevelEditor, x: usize, y: usize) {
+    let id = egui::Id::new("preset_name");
+    let mut name = ui.data_mut(|d| d.get_temp::<String>(id)).unwrap_or_default();
+    ui.horizontal(|ui| {
+        ui.add(egui::TextEdit::singleline(&mut name).hint_text("Preset name").desired_width(120.0));
+        if ui.add_enabled(!name.trim().is_empty(), egui::Button::new("Save as preset")).clicked() {
+            editor.save_preset_from_tile(x, y, &name);
+            name.clear();
+        }
+    });
+    ui.data_mut(|d| d.insert_temp(id, name));
+}
@@ -0,0 +1,116 @@
+use egui_macroquad::egui::{self, Context, Color32};
+use egui_macroquad::macroquad::prelude::vec2;
+use crate::editor::{LevelEditor, LibraryEntry};
+use crate::tile::{TileRegistry, TileType};
+
+// Drag payload for a library module; dropped on the module list or the level it becomes a new module
+pub struct LibraryDrag(pub usize);
+
+pub fn show_library_view(egui_ctx: &Context, editor: &mut LevelEditor) {
+    egui::SidePanel::left("module_library_panel")
+        .resizable(true)
+        .default_width(240.0)
+        .show(egui_ctx, |ui| {
+            ui.heading("Module Library");
+            ui.separator();
+            save_module_row(ui, editor);
+            if let Some(note) = editor.library_note() { ui.label(note); }
+            ui.separator();
+
+            let filter_id = egui::Id::new("library_filter");
+            let mut filter = ui.data_mut(|d| d.get_temp::<String>(filter_id)).unwrap_or_default();
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut filter).hint_text("Filter by name or tag").desired_width(150.0));
+                if ui.button("Reload").on_hover_text(editor.library().dir().display().to_string()).clicked() {
+                    editor.reload_library();
+                }
+            });
+            ui.data_mut(|d| d.insert_temp(filter_id, filter.clone()));
+            for error in editor.library().errors() {
+                ui.colored_label(Color32::RED, error);
+            }
+            ui.weak("Drag a module onto the level or the module list to insert it");
+
+            let mut insert_at_end: Option<usize> = None;
+            let mut delete: Option<usize> = None;
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                let registry = editor.level().registry();
+                for (i, entry) in editor.library().entries().iter().enumerate().filter(|(_, e)| e.module.matches(&filter)) {
+                    let texture = thumbnail(ui, registry, entry);
+                    ui.dnd_drag_source(egui::Id::new(("library_drag", i)), LibraryDrag(i), |ui| {
+                        ui.horizontal(|ui| {
+                            let clip = &entry.module.clip;
+                            let scale = (96.0 / clip.width as f32).min(48.0 / clip.height.max(1) as f32).min(4.0);
+                            ui.image((texture.id(), egui::vec2(clip.width as f32 * scale, clip.height as f32 * scale)));
+                            ui.vertical(|ui| {
+                                ui.strong(&entry.module.name);
+                                ui.weak(format!("{} x {}", clip.width, clip.height));
+                                if !entry.module.tags.is_empty() { ui.weak(entry.module.tags.join(", ")); }
+                            });
+                        });
+                    });
+                    ui.horizontal(|ui| {
+                        if ui.small_button("Insert at end").clicked() { insert_at_end = Some(i); }
+                        if ui.small_button("Delete").clicked() { delete = Some(i); }
+                    });
+                    ui.separator();
+                }
+            });
+            if let Some(i) = insert_at_end { editor.insert_library_module(i, editor.modules().len()); }
+            if let Some(i) = delete { editor.delete_library_module(i); }
+        });
+}
+
+// A library module let go over the level goes in at the nearest module border
+pub fn handle_level_drop(egui_ctx: &Context, editor: &mut LevelEditor) {
+    if egui_ctx.is_pointer_over_area() || !egui_ctx.input(|i| i.pointer.any_released()) { return; }
+    let Some(pos) = egui_ctx.input(|i| i.pointer.interact_pos()) else { return };
+    if let Some(drag) = egui::DragAndDrop::take_payload::<LibraryDrag>(egui_ctx) {
+        let ppp = egui_ctx.pixels_per_point();
+        let index = editor.module_insert_index_at(vec2(pos.x * ppp, pos.y * ppp));
+        editor.insert_library_module(drag.0, index);
+    }
+}
+
+fn save_module_row(ui: &mut egui::Ui, editor: &mut LevelEditor) {
+    let (module_id, name_id, tags_id) = (egui::Id::new("library_save_module"), egui::Id::new("library_save_name"), egui::Id::new("library_save_tags"));
+    let mut module = ui.data_mut(|d| d.get_temp::<usize>(module_id)).unwrap_or(0).min(editor.modules().len().saturating_sub(1));
+    let mut name = ui.data_mut(|d| d.get_temp::<String>(name_id)).unwrap_or_default();
+    let mut tags = ui.data_mut(|d| d.get_temp::<String>(tags_id)).unwrap_or_default();
+    egui::ComboBox::from_id_salt("library_save_module_combo")
+        .selected_text(format!("Module {}", module))
+        .show_ui(ui, |ui| {
+            for i in 0..editor.modules().len() { ui.selectable_value(&mut module, i, format!("Module {}", i)); }
+        });
+    ui.add(egui::TextEdit::singleline(&mut name).hint_text("Name"));
+    ui.add(egui::TextEdit::singleline(&mut tags).hint_text("Tags, comma separated"));
+    let can_save = !name.trim().is_empty() && module < editor.modules().len();
+    if ui.add_enabled(can_save, egui::Button::new("Save to Library")).clicked() {
+        editor.save_module_to_library(module, &name, &tags);
+        name.clear();
+        tags.clear();
+    }
+    ui.data_mut(|d| {
+        d.insert_temp(module_id, module);
+        d.insert_temp(name_id, name);
+        d.insert_temp(tags_id, tags);
+    });
+}
+
+// Tiles drawn in their manifest colours, one pixel per cell; cached until the file changes
+fn thumbnail(ui: &egui::Ui, registry: &TileRegistry, entry: &LibraryEntry) -> egui::TextureHandle {
+    let id = egui::Id::new(("library_thumbnail", &entry.path, entry.revision));
+    if let Some(texture) = ui.data(|d| d.get_temp::<egui::TextureHandle>(id)) { return texture; }
+    let clip = &entry.module.clip;
+    let mut image = egui::ColorImage::new([clip.width, clip.height.max(1)], Color32::from_gray(245));
+    for (y, row) in clip.tiles.iter().enumerate() {
+        for (x, tile) in row.iter().enumerate() {
+            if tile.tile_type == TileType::Air { continue; }
+            let [r, g, b] = registry.color_for(&tile.tile_type).unwrap_or([128, 128, 128]);
+            image[(x, y)] = Color32::from_rgb(r, g, b);
+        }
+    }
+    let texture = ui.ctx().load_texture(format!("library_thumbnail_{}", entry.path.display()), image, egui::TextureOptions::NEAREST);
+    ui.data_mut(|d| d.insert_temp(id, texture.clone()));
+    texture
+}
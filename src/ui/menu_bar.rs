@@ -1,17 +1,24 @@
 use egui_macroquad::egui::{self, Context};
 use crate::editor::LevelEditor;
+use crate::level::{PROJECT_EXTENSION, Segmentation};
 
 pub fn show_menu_bar(egui_ctx: &Context, editor: &mut LevelEditor) {
     egui::TopBottomPanel::top("menu_bar").show(egui_ctx, |ui| {
         ui.horizontal(|ui| {
+            if ui.button("New Level").clicked() { editor.set_show_new_level(true); }
+            ui.separator();
+
             if ui.button("Tile Selector").clicked() {
                 let current_state = editor.show_tile_selector();
                 editor.set_show_tile_selector(!current_state);
             }
 
             if ui.button("Modules View").clicked() { editor.toggle_modules_view(); }
-            if ui.button("Export JSON").clicked() {
-                let destination = rfd::FileDialog::new().add_filter("json", &["json"]).save_file();
+            if ui.button("Module Library").clicked() { editor.toggle_library_view(); }
+            // Validation errors block the export; they are listed in the validation panel
+            if ui.button("Export JSON").clicked() && editor.validate_for_export() {
+                let file_name = format!("{}.json", editor.tab_name(editor.active_tab()));
+                let destination = rfd::FileDialog::new().add_filter("json", &["json"]).set_file_name(file_name).save_file();
                 
                 if destination.is_none() {
                     eprintln!("Invalid destination folder");
@@ -40,33 +47,107 @@ pub fn show_menu_bar(egui_ctx: &Context, editor: &mut LevelEditor) {
                 }
             }
 
-            if ui.button("Import JSON").clicked() {
-                if let Some(path) = rfd::FileDialog::new().add_filter("json", &["json"]).pick_file() {
-                    match std::fs::read_to_string(&path) {
-                        Ok(contents) => {
-                            if let Err(e) = editor.level_import_json(&contents) {
-                                eprintln!("Failed to import json: {}", e);
-                            }
+            if ui.button("Validate").clicked() {
+                editor.run_validation();
+            }
+
+            if ui.button("Assign Missing IDs").clicked() {
+                editor.assign_missing_ids();
+            }
+
+            let mut auto_ids = editor.auto_ids();
+            if ui.checkbox(&mut auto_ids, "Auto IDs").on_hover_text("Give new objects a unique objectID").changed() {
+                editor.set_auto_ids(auto_ids);
+            }
+
+            // Platforms drawn by hand (locked) are not affected
+            let current = editor.segmentation();
+            egui::ComboBox::from_id_salt("segmentation")
+                .selected_text(format!("Platforms: {}", current.name()))
+                .show_ui(ui, |ui| {
+                    for segmentation in Segmentation::ALL {
+                        if ui.selectable_label(current == segmentation, segmentation.name()).clicked() {
+                            editor.set_segmentation(segmentation);
                         }
-                        Err(e) => eprintln!("Failed to read file: {}", e),
+                    }
+                });
+
+            if ui.button("Import JSON").clicked()
+                && let Some(path) = rfd::FileDialog::new().add_filter("json", &["json"]).pick_file() {
+                let outcome = match std::fs::read_to_string(&path) {
+                    Ok(contents) => editor.level_import_json(&contents, &path),
+                    Err(e) => Err(e.into()),
+                };
+                editor.set_import_outcome(outcome);
+            }
+
+            let mut strict = editor.strict_import();
+            if ui.checkbox(&mut strict, "Strict import").changed() {
+                editor.set_strict_import(strict);
+            }
+
+            ui.separator();
+
+            if ui.button("Save Project").clicked() {
+                let file_name = format!("{}.{}", editor.tab_name(editor.active_tab()), PROJECT_EXTENSION);
+                let mut dialog = rfd::FileDialog::new().add_filter("project", &[PROJECT_EXTENSION]).set_file_name(file_name);
+                if let Some(dir) = editor.tab_path().and_then(|p| p.parent()) { dialog = dialog.set_directory(dir); }
+                if let Some(mut path) = dialog.save_file() {
+                    if path.extension().and_then(|e| e.to_str()) != Some(PROJECT_EXTENSION) {
+                        path.set_extension(PROJECT_EXTENSION);
+                    }
+                    let written = editor.project_save_json().map_err(|e| e.to_string())
+                        .and_then(|json| std::fs::write(&path, json).map_err(|e| e.to_string()));
+                    match written {
+                        Ok(()) => editor.project_saved(&path),
+                        Err(e) => editor.set_project_error(format!("Could not save {}: {}", path.display(), e)),
                     }
                 }
             }
-            
+
+            if ui.button("Open Project").clicked()
+                && let Some(path) = rfd::FileDialog::new().add_filter("project", &[PROJECT_EXTENSION]).pick_file() {
+                let opened = std::fs::read_to_string(&path).map_err(|e| e.to_string())
+                    .and_then(|contents| editor.project_load_json(&contents, &path).map_err(|e| e.to_string()));
+                if let Err(e) = opened {
+                    editor.set_project_error(format!("Could not open {}: {}", path.display(), e));
+                }
+            }
+
             ui.separator();
             
             // Undo/Redo buttons
+            let undo_hint = editor.undo_description().map(|d| format!("Undo {}", d)).unwrap_or_default();
             ui.add_enabled_ui(editor.can_undo(), |ui| {
-                if ui.button("Undo (Ctrl+Z)").clicked() {
+                if ui.button("Undo (Ctrl+Z)").on_hover_text(undo_hint).clicked() {
                     editor.undo();
                 }
             });
             
+            let redo_hint = editor.redo_description().map(|d| format!("Redo {}", d)).unwrap_or_default();
             ui.add_enabled_ui(editor.can_redo(), |ui| {
-                if ui.button("Redo (Ctrl+Y)").clicked() {
+                if ui.button("Redo (Ctrl+Y)").on_hover_text(redo_hint).clicked() {
                     editor.redo();
                 }
             });
+
+            ui.separator();
+
+            // Clipboard buttons act on the selector's rectangle
+            let has_selection = editor.selection().is_some();
+            ui.add_enabled_ui(has_selection, |ui| {
+                if ui.button("Copy (Ctrl+C)").clicked() {
+                    editor.copy_selection();
+                }
+                if ui.button("Cut (Ctrl+X)").clicked() {
+                    editor.cut_selection();
+                }
+            });
+            ui.add_enabled_ui(editor.has_clipboard(), |ui| {
+                if ui.button("Paste (Ctrl+V)").clicked() {
+                    editor.paste_clipboard();
+                }
+            });
         });
     });
 }
\ No newline at end of file
@@ -2,10 +2,14 @@ use crate::editor::LevelEditor;
 use crate::ui::module_view::show_module_view;
 use egui_macroquad::egui;
 
+mod import_report_view;
 mod inspector;
+mod library_view;
 mod menu_bar;
 mod module_view;
 mod tile_selector;
+mod validation_view;
+mod workspace_view;
 
 pub struct UI;
 
@@ -13,6 +17,14 @@ impl UI {
     pub fn draw_all(editor: &mut LevelEditor, egui_ctx: &egui::Context) {
         // Draw menu bar
         menu_bar::show_menu_bar(egui_ctx, editor);
+        workspace_view::show_tab_bar(egui_ctx, editor);
+        workspace_view::show_new_level_dialog(egui_ctx, editor);
+        workspace_view::show_close_confirmation(egui_ctx, editor);
+        import_report_view::show_import_report(egui_ctx, editor);
+        import_report_view::show_project_error(egui_ctx, editor);
+        if editor.show_validation() {
+            validation_view::show_validation_panel(egui_ctx, editor);
+        }
 
         // if !editor.show_modules_view()
         //     && editor.show_tile_selector()
@@ -29,11 +41,21 @@ impl UI {
         if editor.show_tile_selector() {
             tile_selector::show_tiles(egui_ctx, editor);
         }
+        if editor.show_library_view() {
+            library_view::show_library_view(egui_ctx, editor);
+        }
         if editor.show_modules_view() {
             // Draw modules panel on left when enabled
             show_module_view(egui_ctx, editor);
         } else if let Some(_tile) = editor.get_selected_tile() {
             inspector::show_inspector(egui_ctx, editor);
         }
+        library_view::handle_level_drop(egui_ctx, editor);
+
+        // Typing into a field or dragging a value is one undo step only while the widget keeps focus
+        // or the drag lasts; afterwards editing the same field again starts a new step
+        if egui_ctx.memory(|m| m.focused().is_none()) && egui_ctx.dragged_id().is_none() {
+            editor.end_merging_edits();
+        }
     }
 }
@@ -1,5 +1,7 @@
 use egui_macroquad::egui::{self, Context};
 use crate::editor::LevelEditor;
+use crate::level::{HeightEdge, MAX_LEVEL_HEIGHT};
+use crate::ui::library_view::LibraryDrag;
 
 
 pub fn show_module_view(egui_ctx: &Context, editor: &mut LevelEditor) {
@@ -10,29 +12,147 @@ pub fn show_module_view(egui_ctx: &Context, editor: &mut LevelEditor) {
                     ui.heading("Modules");
                     ui.separator();
                     ui.label(format!("Level width: {}", editor.level_width()));
+                    level_height_row(ui, editor);
+                    level_theme_row(ui, editor);
                     ui.separator();
-                    // Modules spans list
-                    let mut to_remove: Option<usize> = None;
+                    // Modules spans list; drag a row by its handle to move the module with its content
+                    let mut action: Option<ModuleAction> = None;
                     let modules_snapshot = editor.modules().clone();
                     for (i, span) in modules_snapshot.iter().copied().enumerate() {
                         let mut span_mut = span as i32;
-                        ui.horizontal(|ui| {
-                            ui.label(format!("Module {} span:", i));
+                        let row = ui.horizontal(|ui| {
+                            ui.dnd_drag_source(egui::Id::new(("module_drag", i)), i, |ui| { ui.label("::"); })
+                                .response.on_hover_text("Drag to reorder");
+                            let name = editor.level().module_properties(i).name;
+                            if name.is_empty() { ui.label(format!("Module {} span:", i)); } else { ui.label(format!("Module {} ({}) span:", i, name)); }
                             ui.add(egui::DragValue::new(&mut span_mut).range(1..=100000));
-                            if ui.button("Remove").clicked() {
-                                to_remove = Some(i);
-                            }
+                        }).response;
+                        ui.horizontal(|ui| {
+                            if ui.small_button("Insert before").clicked() { action = Some(ModuleAction::Insert(i)); }
+                            if ui.small_button("Duplicate").clicked() { action = Some(ModuleAction::Duplicate(i)); }
+                            if ui.small_button("Remove").clicked() { action = Some(ModuleAction::Remove(i)); }
+                        });
+                        egui::CollapsingHeader::new("Properties").id_salt(("module_properties", i)).show(ui, |ui| {
+                            module_properties_editor(ui, editor, i);
                         });
                         if span_mut as usize != span {
                             editor.set_module_span(i, span_mut.max(1) as usize);
                         }
+                        if let Some(from) = row.dnd_hover_payload::<usize>() {
+                            // Mark where the dragged module will land
+                            let y = if *from < i { row.rect.bottom() } else { row.rect.top() };
+                            ui.painter().hline(row.rect.x_range(), y, ui.visuals().selection.stroke);
+                        }
+                        if let Some(from) = row.dnd_release_payload::<usize>() {
+                            action = Some(ModuleAction::Move(*from, i));
+                        }
+                        // Library modules dropped on a row go in before it
+                        if row.dnd_hover_payload::<LibraryDrag>().is_some() {
+                            ui.painter().hline(row.rect.x_range(), row.rect.top(), ui.visuals().selection.stroke);
+                        }
+                        if let Some(drag) = row.dnd_release_payload::<LibraryDrag>() {
+                            action = Some(ModuleAction::InsertLibrary(drag.0, i));
+                        }
                     }
-                    if let Some(idx) = to_remove {
-                        editor.remove_module(idx);
+                    match action {
+                        Some(ModuleAction::Insert(idx)) => editor.insert_module(idx, 10),
+                        Some(ModuleAction::Duplicate(idx)) => editor.duplicate_module(idx),
+                        Some(ModuleAction::Remove(idx)) => editor.remove_module(idx),
+                        Some(ModuleAction::Move(from, to)) => editor.move_module(from, to),
+                        Some(ModuleAction::InsertLibrary(entry, idx)) => editor.insert_library_module(entry, idx),
+                        None => {}
                     }
                     ui.separator();
                     if ui.button("Add Module").clicked() {
                         editor.add_module(10);
                     }
                 });
-}
\ No newline at end of file
+}
+
+// Rows are added or removed at the chosen edge; Top keeps every object's game position
+fn level_height_row(ui: &mut egui::Ui, editor: &mut LevelEditor) {
+    let edge_id = egui::Id::new("level_height_edge");
+    let mut edge = ui.data_mut(|d| d.get_temp::<HeightEdge>(edge_id)).unwrap_or(HeightEdge::Top);
+    let mut height = editor.level_height();
+    ui.horizontal(|ui| {
+        ui.label("Level height:");
+        ui.add(egui::DragValue::new(&mut height).range(1..=MAX_LEVEL_HEIGHT));
+    });
+    ui.horizontal(|ui| {
+        ui.label("Resize at:");
+        ui.radio_value(&mut edge, HeightEdge::Top, "Top");
+        ui.radio_value(&mut edge, HeightEdge::Bottom, "Bottom");
+    });
+    ui.data_mut(|d| d.insert_temp(edge_id, edge));
+    if height != editor.level_height() {
+        editor.set_level_height(height, edge);
+    }
+}
+
+// Texture theme of the whole level; a module's own textureTheme overrides it
+fn level_theme_row(ui: &mut egui::Ui, editor: &mut LevelEditor) {
+    let mut theme = editor.level_theme().to_string();
+    ui.horizontal(|ui| {
+        ui.label("Level theme:");
+        ui.add(egui::TextEdit::singleline(&mut theme).desired_width(120.0));
+    });
+    if theme != editor.level_theme() {
+        editor.set_level_theme(&theme);
+    }
+}
+
+// Name, difficulty, tags, theme and music cue of one module. Tags are typed as a comma separated
+// list and applied when the field loses focus, so a trailing comma doesn't get trimmed away mid-typing
+fn module_properties_editor(ui: &mut egui::Ui, editor: &mut LevelEditor, index: usize) {
+    let mut properties = editor.level().module_properties(index);
+    egui::Grid::new(("module_properties_grid", index)).num_columns(2).show(ui, |ui| {
+        ui.label("Name");
+        ui.text_edit_singleline(&mut properties.name);
+        ui.end_row();
+
+        ui.label("Difficulty");
+        ui.horizontal(|ui| {
+            let mut set = properties.difficulty.is_some();
+            if ui.checkbox(&mut set, "").changed() { properties.difficulty = set.then_some(1); }
+            if let Some(difficulty) = properties.difficulty.as_mut() { ui.add(egui::DragValue::new(difficulty).range(0..=10)); }
+        });
+        ui.end_row();
+
+        ui.label("Tags");
+        let tags_id = egui::Id::new(("module_tags_text", index));
+        let mut tags = ui.data_mut(|d| d.get_temp::<String>(tags_id)).unwrap_or_else(|| properties.tags.join(", "));
+        let response = ui.text_edit_singleline(&mut tags);
+        if response.has_focus() {
+            ui.data_mut(|d| d.insert_temp(tags_id, tags));
+        } else {
+            if response.lost_focus() { properties.tags = tags.split(',').map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect(); }
+            ui.data_mut(|d| d.remove::<String>(tags_id));
+        }
+        ui.end_row();
+
+        ui.label("Theme");
+        optional_text(ui, &mut properties.theme, "level theme");
+        ui.end_row();
+
+        ui.label("Music cue");
+        optional_text(ui, &mut properties.music_cue, "none");
+        ui.end_row();
+    });
+    editor.level_mut().set_module_properties(index, properties);
+}
+
+// Text field for an optional value; clearing it unsets the value
+fn optional_text(ui: &mut egui::Ui, value: &mut Option<String>, unset_hint: &str) {
+    let mut text = value.clone().unwrap_or_default();
+    if ui.add(egui::TextEdit::singleline(&mut text).hint_text(unset_hint)).changed() {
+        *value = (!text.trim().is_empty()).then_some(text);
+    }
+}
+
+enum ModuleAction {
+    Insert(usize),
+    Duplicate(usize),
+    Remove(usize),
+    Move(usize, usize),
+    InsertLibrary(usize, usize), // Library entry, module index
+}
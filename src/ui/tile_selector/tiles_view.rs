@@ -1,18 +1,14 @@
 use egui_macroquad::egui::{self, Context};
 
-use crate::{editor::{Mode, LevelEditor}, tile::TileType as OldTileType};
+use crate::{editor::{Mode, DrawingBrushType, LevelEditor}, tile::TileType};
 use crate::tile_type_system::*;
 
 pub fn show_tiles(egui_ctx: &Context, editor: &mut LevelEditor) {
-    // Collect tile types first to avoid borrowing conflicts
-    let all_tile_types: Vec<&TileType> = editor.tile_type_registry().all_tile_types();
+    // Collect tile kinds first to avoid borrowing conflicts
     let mut tiles_by_category: std::collections::HashMap<TileCategory, Vec<(String, String)>> = std::collections::HashMap::new();
-    
-    for tile_type in all_tile_types {
-        if tile_type.id() != "air" { // Skip air tiles
-            let tile_info = (tile_type.id().to_string(), tile_type.display_name().to_string());
-            tiles_by_category.entry(tile_type.category()).or_insert_with(Vec::new).push(tile_info);
-        }
+    for category in [TileCategory::Tiles, TileCategory::Enemies, TileCategory::Collectables, TileCategory::Structures] {
+        let tiles = editor.registry().by_category(category).into_iter().map(|k| (k.key.clone(), k.display_name.clone())).collect();
+        tiles_by_category.insert(category, tiles);
     }
     
     egui::SidePanel::left("tile_selector_panel")
@@ -25,7 +21,7 @@ pub fn show_tiles(egui_ctx: &Context, editor: &mut LevelEditor) {
             let selected_type = editor.selected_tile();
 
             egui::ScrollArea::vertical()
-                .max_height(ui.available_height() - 100.0) // Reserve space for brush selection
+                .max_height(ui.available_height() - 160.0) // Reserve space for mode and brush selection
                 .show(ui, |ui| {
                     ui.vertical(|ui| {
                         // Display tiles by category
@@ -36,15 +32,19 @@ pub fn show_tiles(egui_ctx: &Context, editor: &mut LevelEditor) {
                                     .show(ui, |ui| {
                                         for (tile_id, tile_name) in tiles {
                                             let is_selected = match &selected_type {
-                                                OldTileType::Air => false,
-                                                OldTileType::Custom(k) => k == tile_id,
+                                                TileType::Air => false,
+                                                TileType::Custom(k) => k == tile_id,
                                             };
                                             
                                             if ui.selectable_label(is_selected, tile_name).clicked() {
-                                                editor.set_selected_tile(OldTileType::Custom(tile_id.clone()));
+                                                editor.set_selected_tile(TileType::Custom(tile_id.clone()));
                                                 
-                                        // Automatically switch to drawing mode when selecting any tile
-                                        editor.set_mode(Mode::Drawing);
+                                        // Automatically switch to drawing mode when selecting any tile that allows it
+                                        if allowed_brush(editor, BrushType::Drawing) {
+                                            editor.set_mode(Mode::Drawing);
+                                        } else {
+                                            editor.set_mode(Mode::Selector);
+                                        }
                                             }
                                         }
                                     });
@@ -53,9 +53,9 @@ pub fn show_tiles(egui_ctx: &Context, editor: &mut LevelEditor) {
 
                         ui.separator();
                         // Air option (no category)
-                        let is_air = matches!(selected_type, OldTileType::Air);
+                        let is_air = matches!(selected_type, TileType::Air);
                         if ui.selectable_label(is_air, "Air").clicked() {
-                            editor.set_selected_tile(OldTileType::Air);
+                            editor.set_selected_tile(TileType::Air);
                         }
                     });
                 });
@@ -64,18 +64,94 @@ pub fn show_tiles(egui_ctx: &Context, editor: &mut LevelEditor) {
             ui.heading("Mode");
             ui.separator();
 
-            for mode in [
-                Mode::Drawing,
-                Mode::Selector,
+            for (mode, brush) in [
+                (Mode::Drawing, BrushType::Drawing),
+                (Mode::Selector, BrushType::Selector),
             ] {
                 let is_selected = editor.mode() == mode;
 
                 if ui
-                    .selectable_label(is_selected, mode.name())
+                    .add_enabled(allowed_brush(editor, brush), egui::SelectableLabel::new(is_selected, mode.name()))
                     .clicked()
                 {
                     editor.set_mode(mode);
                 }
             }
+
+            if editor.mode() == Mode::Drawing {
+                ui.separator();
+                ui.horizontal_wrapped(|ui| {
+                    ui.label("Brush:");
+                    for brush in DrawingBrushType::PAINT_BRUSHES {
+                        if ui.selectable_label(editor.paint_brush() == brush, brush.name()).clicked() {
+                            editor.set_paint_brush(brush);
+                        }
+                    }
+                });
+                if editor.paint_brush() == DrawingBrushType::Fill {
+                    let mut within = editor.fill_within_module();
+                    if ui.checkbox(&mut within, "Stay inside module").changed() {
+                        editor.set_fill_within_module(within);
+                    }
+                }
+                if editor.drawing_brush() == DrawingBrushType::Platform {
+                    let mut lock = editor.lock_platforms();
+                    if ui.checkbox(&mut lock, "Lock drawn platforms").on_hover_text("Keep the rectangle as drawn instead of re-segmenting it").changed() {
+                        editor.set_lock_platforms(lock);
+                    }
+                }
+                preset_picker(ui, editor);
+            }
+
+            ui.separator();
+            let mut pick_metadata = editor.pick_metadata();
+            if ui.checkbox(&mut pick_metadata, "Alt+click also picks metadata").changed() {
+                editor.set_pick_metadata(pick_metadata);
+            }
+            if editor.has_stamp() && editor.active_preset().is_none() {
+                ui.horizontal(|ui| {
+                    ui.label("Placing with picked metadata");
+                    if ui.small_button("Reset").clicked() {
+                        editor.clear_stamp();
+                    }
+                });
+            }
         });
 }
+
+// Presets of the selected kind; picking one makes the drawing brush stamp its values
+fn preset_picker(ui: &mut egui::Ui, editor: &mut LevelEditor) {
+    let presets: Vec<(usize, String)> = editor.level().presets_for(&editor.selected_tile()).into_iter().map(|(i, p)| (i, p.name.clone())).collect();
+    if presets.is_empty() {
+        return;
+    }
+    let active = editor.active_preset();
+    let active_name = presets.iter().find(|(i, _)| Some(*i) == active).map_or("Default values", |(_, name)| name.as_str());
+    ui.horizontal(|ui| {
+        ui.label("Preset:");
+        egui::ComboBox::from_id_salt("metadata_preset")
+            .selected_text(active_name)
+            .show_ui(ui, |ui| {
+                if ui.selectable_label(active.is_none(), "Default values").clicked() {
+                    editor.use_preset(None);
+                }
+                for (i, name) in &presets {
+                    if ui.selectable_label(active == Some(*i), name).clicked() {
+                        editor.use_preset(Some(*i));
+                    }
+                }
+            });
+        if let Some(i) = active
+            && ui.small_button("Delete").clicked() {
+            editor.remove_preset(i);
+        }
+    });
+}
+
+// Brushes are restricted per tile type in the manifest; air allows everything
+fn allowed_brush(editor: &LevelEditor, brush: BrushType) -> bool {
+    match editor.selected_tile() {
+        TileType::Air => true,
+        TileType::Custom(k) => editor.registry().get(&k).is_none_or(|t| t.can_use_brush(brush)),
+    }
+}
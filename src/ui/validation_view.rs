@@ -0,0 +1,61 @@
+use egui_macroquad::egui::{self, Context, Color32};
+use crate::editor::LevelEditor;
+use crate::level::Severity;
+
+// Bottom panel listing validation problems; clicking a located problem jumps to its cell
+pub fn show_validation_panel(egui_ctx: &Context, editor: &mut LevelEditor) {
+    let mut focus: Option<(usize, usize)> = None;
+    let mut close = false;
+
+    egui::TopBottomPanel::bottom("validation_panel")
+        .resizable(true)
+        .default_height(140.0)
+        .show(egui_ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading("Validation");
+                if ui.button("Re-run").clicked() {
+                    editor.run_validation();
+                }
+                if ui.button("Close").clicked() {
+                    close = true;
+                }
+            });
+            if let Some(note) = editor.validation_note() {
+                ui.colored_label(Color32::RED, note);
+            }
+            ui.separator();
+
+            let diagnostics = editor.diagnostics();
+            if diagnostics.is_empty() {
+                ui.label("No problems found.");
+                return;
+            }
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for d in diagnostics {
+                    ui.horizontal(|ui| {
+                        let color = match d.severity {
+                            Severity::Error => Color32::RED,
+                            Severity::Warning => Color32::from_rgb(200, 120, 0),
+                        };
+                        ui.colored_label(color, d.severity.name());
+                        ui.label(format!("[{}]", d.rule));
+                        match d.cell {
+                            Some((x, y)) => {
+                                if ui.link(format!("({}, {}) {}", x, y, d.message)).clicked() {
+                                    focus = Some((x, y));
+                                }
+                            }
+                            None => { ui.label(&d.message); }
+                        }
+                    });
+                }
+            });
+        });
+
+    if let Some((x, y)) = focus {
+        editor.focus_cell(x, y);
+    }
+    if close {
+        editor.set_show_validation(false);
+    }
+}
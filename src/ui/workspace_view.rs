@@ -0,0 +1,90 @@
+use egui_macroquad::egui::{self, Context};
+use crate::editor::{LevelEditor, NewLevelSettings};
+use crate::level::MAX_LEVEL_HEIGHT;
+
+// One tab per open level; a '*' marks unsaved changes
+pub fn show_tab_bar(egui_ctx: &Context, editor: &mut LevelEditor) {
+    egui::TopBottomPanel::top("level_tabs").show(egui_ctx, |ui| {
+        let mut switch_to: Option<usize> = None;
+        let mut close: Option<usize> = None;
+        ui.horizontal_wrapped(|ui| {
+            for i in 0..editor.tab_count() {
+                let dirty = if editor.tab_is_dirty(i) { "*" } else { "" };
+                let title = format!("{}{}", editor.tab_name(i), dirty);
+                if ui.selectable_label(i == editor.active_tab(), title).clicked() { switch_to = Some(i); }
+                if editor.tab_count() > 1 && ui.small_button("x").on_hover_text("Close").clicked() { close = Some(i); }
+                ui.separator();
+            }
+            if ui.small_button("+").on_hover_text("New Level").clicked() { editor.set_show_new_level(true); }
+        });
+        if let Some(i) = switch_to { editor.switch_tab(i); }
+        if let Some(i) = close { editor.close_tab(i); }
+    });
+}
+
+// The draft is kept between openings, so a level like the last one is quick to make again
+pub fn show_new_level_dialog(egui_ctx: &Context, editor: &mut LevelEditor) {
+    if !editor.show_new_level() { return; }
+    let draft_id = egui::Id::new("new_level_draft");
+    let mut draft = egui_ctx.data_mut(|d| d.get_temp::<NewLevelSettings>(draft_id)).unwrap_or_default();
+    let mut open = true;
+    let mut create = false;
+    let mut cancel = false;
+    egui::Window::new("New Level")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(egui_ctx, |ui| {
+            egui::Grid::new("new_level_grid").num_columns(2).show(ui, |ui| {
+                ui.label("Name");
+                ui.text_edit_singleline(&mut draft.name);
+                ui.end_row();
+                ui.label("Height");
+                ui.add(egui::DragValue::new(&mut draft.height).range(1..=MAX_LEVEL_HEIGHT));
+                ui.end_row();
+                ui.label("Theme");
+                ui.text_edit_singleline(&mut draft.theme);
+                ui.end_row();
+            });
+            ui.separator();
+            ui.label("Module spans:");
+            let mut remove: Option<usize> = None;
+            for (i, span) in draft.modules.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(format!("Module {}", i));
+                    ui.add(egui::DragValue::new(span).range(1..=1000));
+                    if ui.small_button("Remove").clicked() { remove = Some(i); }
+                });
+            }
+            if let Some(i) = remove { draft.modules.remove(i); }
+            if ui.button("Add Module").clicked() {
+                draft.modules.push(draft.modules.last().copied().unwrap_or(15));
+            }
+            ui.label(format!("Width: {}", draft.modules.iter().sum::<usize>()));
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.add_enabled(!draft.modules.is_empty(), egui::Button::new("Create")).clicked() { create = true; }
+                if ui.button("Cancel").clicked() { cancel = true; }
+            });
+        });
+    if create { editor.new_level(&draft); }
+    if create || cancel || !open { editor.set_show_new_level(false); }
+    egui_ctx.data_mut(|d| d.insert_temp(draft_id, draft));
+}
+
+pub fn show_close_confirmation(egui_ctx: &Context, editor: &mut LevelEditor) {
+    let Some(index) = editor.closing_tab() else { return };
+    let mut choice: Option<bool> = None;
+    egui::Window::new("Unsaved Changes")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .show(egui_ctx, |ui| {
+            ui.label(format!("\"{}\" has unsaved changes. Close it anyway?", editor.tab_name(index)));
+            ui.horizontal(|ui| {
+                if ui.button("Close Without Saving").clicked() { choice = Some(true); }
+                if ui.button("Cancel").clicked() { choice = Some(false); }
+            });
+        });
+    if let Some(close) = choice { editor.confirm_close_tab(close); }
+}